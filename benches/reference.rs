@@ -0,0 +1,124 @@
+use std::{
+    hint::black_box,
+    ops::Range,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use reference_arc::{ArcReference, ArcReferencePool};
+
+const ITERATIONS: u32 = 1_000_000;
+
+fn bench(name: &str, mut f: impl FnMut()) {
+    for _ in 0..ITERATIONS / 10 {
+        f();
+    }
+
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        f();
+    }
+    let elapsed = start.elapsed();
+
+    println!(
+        "{name:<40} {:>10.2} ns/iter",
+        elapsed.as_nanos() as f64 / ITERATIONS as f64
+    );
+}
+
+/// The hand-rolled alternative to an `ArcReference<String, str>`: keeping the owner and
+/// the range of the substring next to each other.
+#[derive(Clone)]
+struct ArcRange {
+    inner: Arc<String>,
+    range: Range<usize>,
+}
+
+impl ArcRange {
+    fn get(&self) -> &str {
+        &self.inner[self.range.clone()]
+    }
+}
+
+fn fan_out(threads: usize, f: impl Fn() + Send + Sync + 'static) -> Duration {
+    let f = Arc::new(f);
+    let start = Instant::now();
+
+    let handles: Vec<_> = (0..threads)
+        .map(|_| {
+            let f = f.clone();
+            std::thread::spawn(move || {
+                for _ in 0..ITERATIONS / threads as u32 {
+                    f();
+                }
+            })
+        })
+        .collect();
+
+    for handle in handles {
+        handle.join().unwrap();
+    }
+
+    start.elapsed()
+}
+
+fn main() {
+    let string = Arc::new(String::from("Hello World!"));
+
+    bench("construct ArcReference<String, str>", || {
+        black_box(ArcReference::new(black_box(&string).clone(), |string| {
+            &string[6..11]
+        }));
+    });
+    let pool = ArcReferencePool::new(string.clone(), 64);
+    bench("acquire from ArcReferencePool", || {
+        black_box(black_box(&pool).acquire(|string| &string[6..11]));
+    });
+    drop(pool);
+
+    bench("construct ArcRange", || {
+        black_box(ArcRange {
+            inner: black_box(&string).clone(),
+            range: 6..11,
+        });
+    });
+
+    let reference = ArcReference::new(string.clone(), |string| &string[6..11]);
+    let range = ArcRange {
+        inner: string.clone(),
+        range: 6..11,
+    };
+
+    bench("clone ArcReference<String, str>", || {
+        black_box(black_box(&reference).clone());
+    });
+    bench("clone ArcRange", || {
+        black_box(black_box(&range).clone());
+    });
+
+    bench("deref ArcReference<String, str>", || {
+        black_box(&**black_box(&reference));
+    });
+    bench("deref ArcRange", || {
+        black_box(black_box(&range).get());
+    });
+
+    let slice = ArcReference::new(Arc::new((0..1024u64).collect::<Vec<_>>()), |vec| &vec[..]);
+
+    bench("iterate ArcReference<Vec<u64>, [u64]>", || {
+        black_box(black_box(&slice).iter().sum::<u64>());
+    });
+
+    for threads in [1, 4, 8] {
+        let reference = reference.clone();
+        let elapsed = fan_out(threads, move || {
+            black_box(black_box(&reference).clone().len());
+        });
+
+        println!(
+            "{:<40} {:>10.2} ns/iter",
+            format!("fan out clone+deref ({threads} threads)"),
+            elapsed.as_nanos() as f64 / ITERATIONS as f64
+        );
+    }
+}
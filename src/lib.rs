@@ -1,53 +1,436 @@
 use std::{
+    any::Any,
+    borrow::{Borrow, Cow},
+    cell::Cell,
+    cmp::Ordering,
+    collections::{hash_map::RandomState, HashMap},
+    error::Error,
     fmt::{Debug, Display},
-    ops::Deref,
+    hash::{BuildHasher, Hash, Hasher},
+    marker::PhantomData,
+    mem::MaybeUninit,
+    ops::{Deref, Range},
     ptr::NonNull,
     rc::Rc,
-    sync::Arc,
+    str::Utf8Error,
+    sync::{
+        atomic::{self, AtomicPtr, AtomicUsize},
+        Arc, Mutex, PoisonError, RwLock,
+    },
 };
 
+#[cfg(feature = "metrics")]
+pub mod metrics;
+
+#[cfg(feature = "metrics")]
+type ArcMetrics = metrics::Guard;
+#[cfg(not(feature = "metrics"))]
+type ArcMetrics = ();
+
 macro_rules! implementation {
-    ($reference_name: ident, $context_name: ident, $rc_type: ident, $multiple_method_name: ident) => {
+    (
+        $reference_name: ident,
+        $weak_reference_name: ident,
+        $context_name: ident,
+        $projector_name: ident,
+        $rc_type: ident,
+        $rc_module: ident,
+        $metrics: ty,
+        $multiple_method_name: ident,
+        $field_method_name: ident
+    ) => {
         pub struct $reference_name<O, R>
         where
+            O: ?Sized,
             R: ?Sized,
         {
             inner: $rc_type<O>,
             ptr: NonNull<R>,
+            metrics: $metrics,
         }
 
         impl<O, R> $reference_name<O, R>
         where
+            O: ?Sized,
             R: ?Sized,
         {
+            fn from_parts(inner: $rc_type<O>, ptr: NonNull<R>) -> Self {
+                Self {
+                    inner,
+                    ptr,
+                    metrics: Default::default(),
+                }
+            }
+
             pub fn new(inner: $rc_type<O>, f: impl FnOnce(&O) -> &R) -> Self {
                 unsafe {
-                    Self {
-                        ptr: NonNull::new_unchecked(f(&inner) as *const R as *mut R),
-                        inner,
-                    }
+                    let ptr = NonNull::new_unchecked(f(&inner) as *const R as *mut R);
+                    Self::from_parts(inner, ptr)
                 }
             }
 
+            /// Projects `inner` and passes the target to `use_it` without creating a
+            /// reference, so the owner's reference count is left untouched.
+            pub fn peek<T>(
+                inner: &$rc_type<O>,
+                f: impl FnOnce(&O) -> &R,
+                use_it: impl FnOnce(&R) -> T,
+            ) -> T {
+                use_it(f(inner))
+            }
+
+            pub fn new_from_weak(
+                weak: &std::$rc_module::Weak<O>,
+                f: impl FnOnce(&O) -> &R,
+            ) -> Option<Self> {
+                Some(Self::new(weak.upgrade()?, f))
+            }
+
             pub fn source(&self) -> &$rc_type<O> {
                 &self.inner
             }
+
+            /// Builds a new owner from the target and projects into it, releasing the
+            /// current owner.
+            pub fn map_via<R2: ?Sized, Scratch>(
+                self,
+                build: impl FnOnce(&R) -> Scratch,
+                project: impl FnOnce(&Scratch) -> &R2,
+            ) -> $reference_name<Scratch, R2> {
+                $reference_name::new($rc_type::new(build(&self)), project)
+            }
+
+            pub fn fork(&self) -> (Self, Self) {
+                (self.clone(), self.clone())
+            }
+
+            /// Returns `n` clones of this reference, incrementing the owner's strong count
+            /// `n` times in a row.
+            pub fn fork_n(&self, n: usize) -> Vec<Self> {
+                (0..n).map(|_| self.clone()).collect()
+            }
+
+            pub fn downgrade(&self) -> $weak_reference_name<O, R> {
+                $weak_reference_name {
+                    inner: $rc_type::downgrade(&self.inner),
+                    ptr: self.ptr,
+                }
+            }
+
+            /// The byte offset of the target within the owner, or `None` if the target does
+            /// not lie inside the owner's own bytes (e.g. because it is on the heap).
+            pub fn target_offset(&self) -> Option<usize> {
+                let owner = $rc_type::as_ptr(&self.inner) as *const u8 as usize;
+                let target = self.ptr.as_ptr() as *const u8 as usize;
+
+                let offset = target.checked_sub(owner)?;
+                let end = offset.checked_add(std::mem::size_of_val(&**self))?;
+
+                (end <= std::mem::size_of_val(&*self.inner)).then_some(offset)
+            }
+
+            pub fn project(&self) -> $projector_name<'_, O, R> {
+                $projector_name {
+                    inner: &self.inner,
+                    target: Some(self),
+                }
+            }
+
+            pub fn reproject_in_place(&mut self, f: impl FnOnce(&O) -> &R) {
+                self.ptr = NonNull::from(f(&self.inner));
+            }
+
+            /// Mutates the owner if this is the only reference to it, then reprojects
+            /// into the (possibly moved) target.
+            ///
+            /// # Aborts
+            ///
+            /// `reproject` also runs if `mutate` panics, so the reference never points into
+            /// data the mutation may have freed. If `reproject` panics, there is no valid
+            /// target left to point to and the process is aborted, even if `mutate`
+            /// returned normally.
+            pub fn with_mut<T>(
+                &mut self,
+                mutate: impl FnOnce(&mut O) -> T,
+                reproject: impl FnOnce(&O) -> &R,
+            ) -> Option<T> {
+                struct Reproject<'a, O, R, F>
+                where
+                    O: ?Sized,
+                    R: ?Sized,
+                    F: FnOnce(&O) -> &R,
+                {
+                    reference: &'a mut $reference_name<O, R>,
+                    reproject: Option<F>,
+                }
+
+                impl<O, R, F> Drop for Reproject<'_, O, R, F>
+                where
+                    O: ?Sized,
+                    R: ?Sized,
+                    F: FnOnce(&O) -> &R,
+                {
+                    fn drop(&mut self) {
+                        let reproject = self.reproject.take().unwrap();
+                        let inner = &self.reference.inner;
+                        match std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                            NonNull::from(reproject(inner))
+                        })) {
+                            Ok(ptr) => self.reference.ptr = ptr,
+                            Err(_) => std::process::abort(),
+                        }
+                    }
+                }
+
+                $rc_type::get_mut(&mut self.inner)?;
+
+                let guard = Reproject {
+                    reference: self,
+                    reproject: Some(reproject),
+                };
+                Some(mutate($rc_type::get_mut(&mut guard.reference.inner).unwrap()))
+            }
+
+            /// Leaks the owner and returns a `'static` reference to the target, but only if
+            /// this is the only strong reference to the owner.
+            pub fn try_leak(self) -> Result<&'static R, Self>
+            where
+                O: 'static,
+                R: 'static,
+            {
+                if $rc_type::strong_count(&self.inner) != 1 {
+                    return Err(self);
+                }
+
+                std::mem::forget(self.inner);
+                Ok(unsafe { &*self.ptr.as_ptr() })
+            }
+
+            /// Returns an owned copy of the target as a `Cow` that outlives the owner.
+            pub fn into_cow_owned(self) -> Cow<'static, R>
+            where
+                R: ToOwned + 'static,
+                R::Owned: 'static,
+            {
+                Cow::Owned((*self).to_owned())
+            }
+
+            pub fn cmp_by(&self, other: &Self, cmp: impl FnOnce(&R, &R) -> Ordering) -> Ordering {
+                cmp(self, other)
+            }
+
+            /// Projects further into the current target, failing with a [`TraceError`]
+            /// naming `label` if `f` returns `None`.
+            ///
+            /// On success, `label` is recorded in the returned [`Traced`] reference, so
+            /// that later steps chained with [`TryMapTraced`] report the full path.
+            pub fn try_map_traced<R2: ?Sized>(
+                self,
+                label: &'static str,
+                f: impl FnOnce(&R) -> Option<&R2>,
+            ) -> Result<Traced<$reference_name<O, R2>>, TraceError> {
+                Traced {
+                    reference: self,
+                    path: Vec::new(),
+                }
+                .try_map_traced(label, f)
+            }
+        }
+
+        impl<O, R> $reference_name<O, R>
+        where
+            O: ?Sized,
+        {
+            /// Projects into a `MaybeUninit<R>` field of the owner that has been
+            /// initialized after the owner was constructed.
+            ///
+            /// # Safety
+            ///
+            /// The projected `MaybeUninit<R>` must be fully initialized and must not be
+            /// de-initialized for as long as the returned reference (or any clone of it)
+            /// is alive.
+            pub unsafe fn assume_init_ref(
+                inner: $rc_type<O>,
+                f: impl FnOnce(&O) -> &MaybeUninit<R>,
+            ) -> Self {
+                let ptr = NonNull::from(f(&inner)).cast();
+                Self::from_parts(inner, ptr)
+            }
+
+            /// Rebuilds a reference from an offset returned by `target_offset`.
+            ///
+            /// # Safety
+            ///
+            /// A valid `R` must live at `offset` bytes into the owner, e.g. because the
+            /// offset was computed for the same field of another owner of the same type.
+            ///
+            /// Field offsets of owners that aren't `#[repr(C)]` are only stable within one
+            /// compiled binary, so an offset stored by a different build, or for such an
+            /// owner, may not point to the same field.
+            pub unsafe fn from_owner_offset(inner: $rc_type<O>, offset: usize) -> Self {
+                let ptr = ($rc_type::as_ptr(&inner) as *const u8).add(offset) as *mut R;
+
+                Self::from_parts(inner, NonNull::new_unchecked(ptr))
+            }
+        }
+
+        #[cfg(feature = "validate")]
+        impl<O, R> $reference_name<O, R>
+        where
+            O: ?Sized,
+        {
+            /// Projects into a target reinterpreted from the owner's bytes, returning the
+            /// owner back if the pointer is not aligned for `R`.
+            ///
+            /// # Safety
+            ///
+            /// Apart from its alignment, which is checked, the pointer returned by `f` must
+            /// point to a valid `R` that lives inside the owner (or as long as it).
+            pub unsafe fn try_new_aligned(
+                inner: $rc_type<O>,
+                f: impl FnOnce(&O) -> *const R,
+            ) -> Result<Self, $rc_type<O>> {
+                let ptr = f(&inner);
+
+                if !ptr.is_aligned() {
+                    return Err(inner);
+                }
+
+                Ok(Self::from_parts(inner, NonNull::new_unchecked(ptr as *mut R)))
+            }
+        }
+
+        #[cfg(feature = "track-drops")]
+        impl<O, X> $reference_name<O, $reference_name<O, X>>
+        where
+            O: ?Sized,
+            X: ?Sized,
+        {
+            /// Panics if the target is a reference to the same owner.
+            ///
+            /// The target is reachable from the owner (inline or e.g. through a `Vec`), so
+            /// a reference it holds to that owner forms a reference counting cycle, and the
+            /// owner is never freed.
+            pub fn assert_acyclic(&self) {
+                assert!(
+                    !$rc_type::ptr_eq(&self.inner, &(**self).inner),
+                    "reference stored inside its own owner forms a cycle",
+                );
+            }
+        }
+
+        call_implementation!($reference_name);
+
+        downcast_target_implementation!($reference_name, dyn Any);
+        downcast_target_implementation!($reference_name, dyn Any + Send);
+        downcast_target_implementation!($reference_name, dyn Any + Send + Sync);
+
+        impl<O> $reference_name<O, O> {
+            /// Moves the owner into a `Box` if the target is the owner itself and this is
+            /// the only reference to it.
+            pub fn try_unwrap_box(self) -> Result<Box<O>, Self> {
+                if !std::ptr::eq(self.ptr.as_ptr(), $rc_type::as_ptr(&self.inner)) {
+                    return Err(self);
+                }
+
+                match $rc_type::try_unwrap(self.inner) {
+                    Ok(owner) => Ok(Box::new(owner)),
+                    Err(inner) => Err(Self {
+                        inner,
+                        ptr: self.ptr,
+                        metrics: self.metrics,
+                    }),
+                }
+            }
+
+            /// Like `into_cow_owned`, but moves the target out of the owner instead of
+            /// cloning it if the target is the owner itself and this is the only reference
+            /// to it.
+            pub fn into_cow_unwrapped(self) -> Cow<'static, O>
+            where
+                O: Clone,
+            {
+                if !std::ptr::eq(self.ptr.as_ptr(), $rc_type::as_ptr(&self.inner)) {
+                    return Cow::Owned((*self).clone());
+                }
+
+                match $rc_type::try_unwrap(self.inner) {
+                    Ok(owner) => Cow::Owned(owner),
+                    Err(inner) => Cow::Owned((*inner).clone()),
+                }
+            }
+        }
+
+        impl<O, R> Traced<$reference_name<O, R>>
+        where
+            O: ?Sized,
+            R: ?Sized,
+        {
+            fn try_map_traced<R2: ?Sized>(
+                self,
+                label: &'static str,
+                f: impl FnOnce(&R) -> Option<&R2>,
+            ) -> Result<Traced<$reference_name<O, R2>>, TraceError> {
+                let Traced {
+                    reference,
+                    mut path,
+                } = self;
+                path.push(label);
+
+                let Some(target) = f(&reference) else {
+                    return Err(TraceError {
+                        path,
+                        skipped: Vec::new(),
+                    });
+                };
+                let ptr = NonNull::from(target);
+
+                Ok(Traced {
+                    reference: $reference_name::from_parts(reference.inner, ptr),
+                    path,
+                })
+            }
+        }
+
+        impl<O, R> TryMapTraced<O, R> for Result<Traced<$reference_name<O, R>>, TraceError>
+        where
+            O: ?Sized,
+            R: ?Sized,
+        {
+            type Output<R2: ?Sized> = Traced<$reference_name<O, R2>>;
+
+            fn try_map_traced<R2: ?Sized>(
+                self,
+                label: &'static str,
+                f: impl FnOnce(&R) -> Option<&R2>,
+            ) -> Result<Self::Output<R2>, TraceError> {
+                match self {
+                    Ok(traced) => traced.try_map_traced(label, f),
+                    Err(mut error) => {
+                        error.skipped.push(label);
+                        Err(error)
+                    }
+                }
+            }
         }
 
         impl<O, R> Clone for $reference_name<O, R>
         where
+            O: ?Sized,
             R: ?Sized,
         {
             fn clone(&self) -> Self {
                 Self {
                     inner: self.inner.clone(),
                     ptr: self.ptr,
+                    metrics: self.metrics.clone(),
                 }
             }
         }
 
         impl<O, R> Deref for $reference_name<O, R>
         where
+            O: ?Sized,
             R: ?Sized,
         {
             type Target = R;
@@ -58,6 +441,7 @@ macro_rules! implementation {
         }
         impl<O, R> AsRef<R> for $reference_name<O, R>
         where
+            O: ?Sized,
             R: ?Sized,
         {
             fn as_ref(&self) -> &R {
@@ -67,6 +451,7 @@ macro_rules! implementation {
 
         impl<O, R> Display for $reference_name<O, R>
         where
+            O: ?Sized,
             R: ?Sized + Display,
         {
             fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -76,6 +461,7 @@ macro_rules! implementation {
 
         impl<O, R> Debug for $reference_name<O, R>
         where
+            O: ?Sized,
             R: ?Sized + Debug,
         {
             fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -83,210 +469,3382 @@ macro_rules! implementation {
             }
         }
 
-        pub struct $context_name<'a, T> {
+        impl<O, O2, R, R2> PartialEq<$reference_name<O2, R2>> for $reference_name<O, R>
+        where
+            O: ?Sized,
+            O2: ?Sized,
+            R: ?Sized + PartialEq<R2>,
+            R2: ?Sized,
+        {
+            fn eq(&self, other: &$reference_name<O2, R2>) -> bool {
+                **self == **other
+            }
+        }
+
+        impl<O, R> Eq for $reference_name<O, R>
+        where
+            O: ?Sized,
+            R: ?Sized + Eq,
+        {
+        }
+
+        impl<O, O2, R, R2> PartialOrd<$reference_name<O2, R2>> for $reference_name<O, R>
+        where
+            O: ?Sized,
+            O2: ?Sized,
+            R: ?Sized + PartialOrd<R2>,
+            R2: ?Sized,
+        {
+            fn partial_cmp(&self, other: &$reference_name<O2, R2>) -> Option<Ordering> {
+                (**self).partial_cmp(&**other)
+            }
+        }
+
+        impl<O, R> Ord for $reference_name<O, R>
+        where
+            O: ?Sized,
+            R: ?Sized + Ord,
+        {
+            fn cmp(&self, other: &Self) -> Ordering {
+                (**self).cmp(&**other)
+            }
+        }
+
+        impl<O, R> Hash for $reference_name<O, R>
+        where
+            O: ?Sized,
+            R: ?Sized + Hash,
+        {
+            fn hash<H: Hasher>(&self, state: &mut H) {
+                (**self).hash(state)
+            }
+        }
+
+        impl<O, R> AnyReference<R> for $reference_name<O, R>
+        where
+            O: ?Sized,
+            R: ?Sized,
+        {
+            fn target(&self) -> &R {
+                self
+            }
+        }
+
+        pub struct $weak_reference_name<O, R>
+        where
+            O: ?Sized,
+            R: ?Sized,
+        {
+            inner: std::$rc_module::Weak<O>,
+            ptr: NonNull<R>,
+        }
+
+        impl<O, R> $weak_reference_name<O, R>
+        where
+            O: ?Sized,
+            R: ?Sized,
+        {
+            pub fn upgrade(&self) -> Option<$reference_name<O, R>> {
+                Some($reference_name::from_parts(self.inner.upgrade()?, self.ptr))
+            }
+
+            pub fn strong_count(&self) -> usize {
+                self.inner.strong_count()
+            }
+        }
+
+        impl<O, R> Debug for $weak_reference_name<O, R>
+        where
+            O: ?Sized,
+            R: ?Sized + Debug,
+        {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                // Only the `Weak` is upgraded, so formatting doesn't count as creating a
+                // reference.
+                match self.inner.upgrade() {
+                    // SAFETY: the owner, which keeps the target alive, is held by `_inner`.
+                    Some(_inner) => <R as Debug>::fmt(unsafe { self.ptr.as_ref() }, f),
+                    None => write!(f, "{}(<dead>)", stringify!($weak_reference_name)),
+                }
+            }
+        }
+
+        impl<O, R> Clone for $weak_reference_name<O, R>
+        where
+            O: ?Sized,
+            R: ?Sized,
+        {
+            fn clone(&self) -> Self {
+                Self {
+                    inner: self.inner.clone(),
+                    ptr: self.ptr,
+                }
+            }
+        }
+
+        /// Creates references into the owner passed to
+        #[doc = concat!("[`", stringify!($multiple_method_name), "`].")]
+        ///
+        /// The lifetime `'a` is invariant and only known to outlive the closure, so only
+        /// references that live at least as long as the owner borrow are accepted.
+        /// References to values local to the closure are rejected:
+        ///
+        #[doc = concat!(
+            "```compile_fail\n",
+            "use std::{rc::Rc, sync::Arc};\n",
+            "use reference_arc::", stringify!($multiple_method_name), ";\n",
+            "\n",
+            "let owner = ", stringify!($rc_type), "::new(42u8);\n",
+            "\n",
+            "let reference = ", stringify!($multiple_method_name), "(&owner, |ctx, _value| {\n",
+            "    let local = 42u8;\n",
+            "    ctx.new_reference(&local)\n",
+            "});\n",
+            "```",
+        )]
+        pub struct $context_name<'a, T>
+        where
+            T: ?Sized,
+        {
             inner: &'a $rc_type<T>,
+            brand: PhantomData<fn(&'a ()) -> &'a ()>,
         }
 
-        impl<'a, O> $context_name<'a, O> {
-            pub fn new_reference<R>(&'a self, r: &'a R) -> $reference_name<O, R> {
+        impl<'a, O> $context_name<'a, O>
+        where
+            O: ?Sized,
+        {
+            pub fn new_reference<R>(&self, r: &'a R) -> $reference_name<O, R> {
                 unsafe {
-                    $reference_name {
-                        ptr: NonNull::new_unchecked(r as *const R as *mut R),
-                        inner: self.inner.clone(),
-                    }
+                    let ptr = NonNull::new_unchecked(r as *const R as *mut R);
+                    $reference_name::from_parts(self.inner.clone(), ptr)
+                }
+            }
+        }
+
+        /// A chain of projections starting at a reference, returned by `project`.
+        ///
+        /// The owner is only cloned once the chain is finished with `build`. Like `new`,
+        /// each step must borrow from its input, so values outside the owner are rejected:
+        ///
+        #[doc = concat!(
+            "```compile_fail\n",
+            "use std::{rc::Rc, sync::Arc};\n",
+            "use reference_arc::", stringify!($reference_name), ";\n",
+            "\n",
+            "let root = ", stringify!($reference_name), "::new(", stringify!($rc_type), "::new(String::new()), |string| string.as_str());\n",
+            "let local = String::from(\"local\");\n",
+            "\n",
+            "let reference = root.project().field(|_| local.as_str()).build().unwrap();\n",
+            "drop(local);\n",
+            "println!(\"{}\", &*reference);\n",
+            "```",
+        )]
+        pub struct $projector_name<'a, O, R>
+        where
+            O: ?Sized,
+            R: ?Sized,
+        {
+            inner: &'a $rc_type<O>,
+            target: Option<&'a R>,
+        }
+
+        impl<'a, O, R> $projector_name<'a, O, R>
+        where
+            O: ?Sized,
+            R: ?Sized,
+        {
+            pub fn field<R2: ?Sized>(
+                self,
+                f: impl for<'b> FnOnce(&'b R) -> &'b R2,
+            ) -> $projector_name<'a, O, R2> {
+                $projector_name {
+                    inner: self.inner,
+                    target: self.target.map(f),
+                }
+            }
+
+            pub fn index<T>(self, index: usize) -> $projector_name<'a, O, T>
+            where
+                R: AsRef<[T]>,
+            {
+                $projector_name {
+                    inner: self.inner,
+                    target: self.target.and_then(|target| target.as_ref().get(index)),
+                }
+            }
+
+            pub fn build(self) -> Option<$reference_name<O, R>> {
+                Some($reference_name::from_parts(self.inner.clone(), NonNull::from(self.target?)))
+            }
+        }
+
+        impl<'a, O, T> $projector_name<'a, O, Option<T>>
+        where
+            O: ?Sized,
+        {
+            pub fn some(self) -> $projector_name<'a, O, T> {
+                $projector_name {
+                    inner: self.inner,
+                    target: self.target.and_then(Option::as_ref),
+                }
+            }
+        }
+
+        impl<'a, O, T, E> $projector_name<'a, O, Result<T, E>>
+        where
+            O: ?Sized,
+        {
+            pub fn ok(self) -> $projector_name<'a, O, T> {
+                $projector_name {
+                    inner: self.inner,
+                    target: self.target.and_then(|target| target.as_ref().ok()),
                 }
             }
         }
 
         pub fn $multiple_method_name<T, R>(
             arc: &$rc_type<T>,
-            f: impl FnOnce($context_name<T>, &T) -> R,
-        ) -> R {
-            f($context_name { inner: &arc }, &arc)
+            f: impl for<'a> FnOnce($context_name<'a, T>, &'a T) -> R,
+        ) -> R
+        where
+            T: ?Sized,
+        {
+            f(
+                $context_name {
+                    inner: arc,
+                    brand: PhantomData,
+                },
+                arc,
+            )
         }
-    };
-}
 
-implementation!(RcReference, RcMultipleContext, Rc, rc_multiple);
-implementation!(ArcReference, ArcMultipleContext, Arc, arc_multiple);
+        pub fn $field_method_name<T, R>(
+            arc: &$rc_type<T>,
+            f: impl FnOnce(&T) -> &R,
+        ) -> $reference_name<T, R>
+        where
+            T: ?Sized,
+            R: ?Sized,
+        {
+            $reference_name::new(arc.clone(), f)
+        }
 
-unsafe impl<O, R> Send for ArcReference<O, R>
-where
-    R: ?Sized,
-    Arc<O>: Send,
-    for<'r> &'r R: Send,
-{
+        split_tuple_implementation!($reference_name);
+    };
 }
 
-unsafe impl<O, R> Sync for ArcReference<O, R>
-where
-    R: ?Sized,
-    Arc<O>: Sync,
-    for<'r> &'r R: Sync,
-{
+/// A reference returned by `try_map_traced`, along with the labels of the projection
+/// steps that led to it.
+#[derive(Debug, Clone)]
+pub struct Traced<T> {
+    reference: T,
+    path: Vec<&'static str>,
 }
 
-#[cfg(test)]
-mod tests {
-    use std::sync::Barrier;
-
-    use super::*;
+impl<T> Traced<T> {
+    /// The labels of every projection step taken so far.
+    pub fn path(&self) -> &[&'static str] {
+        &self.path
+    }
 
-    #[test]
-    fn rc() {
-        let rc = Rc::new(String::from("Hello World!"));
+    pub fn into_inner(self) -> T {
+        self.reference
+    }
+}
 
-        let hello = RcReference::new(rc.clone(), |string| &string[0..5]);
-        let world = RcReference::new(rc.clone(), |string| &string[6..11]);
+impl<T> Deref for Traced<T> {
+    type Target = T;
 
-        assert_eq!(format!("{hello} {world}"), "Hello World");
+    fn deref(&self) -> &Self::Target {
+        &self.reference
     }
+}
 
-    #[test]
-    fn arc() {
-        let arc = Arc::new(String::from("Hello World!"));
+/// The error returned by `try_map_traced` when a projection step fails.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TraceError {
+    path: Vec<&'static str>,
+    skipped: Vec<&'static str>,
+}
 
-        let hello = ArcReference::new(arc.clone(), |string| &string[0..5]);
-        let world = ArcReference::new(arc.clone(), |string| &string[6..11]);
+impl TraceError {
+    /// The label of the projection step that failed.
+    pub fn failed_at(&self) -> &'static str {
+        self.path[self.path.len() - 1]
+    }
 
-        assert_eq!(format!("{hello} {world}"), "Hello World");
+    /// The labels of the steps that succeeded, followed by the label of the failed step.
+    pub fn path(&self) -> &[&'static str] {
+        &self.path
     }
 
-    #[test]
-    fn drop_arc() {
-        let arc = Arc::new(String::from("Hello World!"));
+    /// The labels of every step that was skipped because of the failure.
+    pub fn skipped(&self) -> &[&'static str] {
+        &self.skipped
+    }
+}
 
-        let hello = ArcReference::new(arc.clone(), |string| &string[0..5]);
-        let world = ArcReference::new(arc.clone(), |string| &string[6..11]);
+impl Display for TraceError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "projection failed at `{}`", self.path.join(" → "))?;
+
+        if !self.skipped.is_empty() {
+            write!(f, " (skipped {})", self.skipped.join(" → "))?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Error for TraceError {}
+
+/// Allows chaining `try_map_traced` calls on the result of a previous one.
+pub trait TryMapTraced<O, R>
+where
+    O: ?Sized,
+    R: ?Sized,
+{
+    type Output<R2: ?Sized>;
+
+    fn try_map_traced<R2: ?Sized>(
+        self,
+        label: &'static str,
+        f: impl FnOnce(&R) -> Option<&R2>,
+    ) -> Result<Self::Output<R2>, TraceError>;
+}
+
+/// A reference of any kind to an `R`, implemented by both [`RcReference`] and
+/// [`ArcReference`].
+///
+/// `dyn AnyReference<R>` compares and hashes by target value, so references of both
+/// kinds and with different owners can be stored in one collection.
+pub trait AnyReference<R>
+where
+    R: ?Sized,
+{
+    fn target(&self) -> &R;
+}
+
+impl<R> PartialEq for dyn AnyReference<R> + '_
+where
+    R: ?Sized + PartialEq,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.target() == other.target()
+    }
+}
+
+impl<R> Eq for dyn AnyReference<R> + '_ where R: ?Sized + Eq {}
+
+impl<R> PartialOrd for dyn AnyReference<R> + '_
+where
+    R: ?Sized + PartialOrd,
+{
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.target().partial_cmp(other.target())
+    }
+}
+
+impl<R> Ord for dyn AnyReference<R> + '_
+where
+    R: ?Sized + Ord,
+{
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.target().cmp(other.target())
+    }
+}
+
+impl<R> Hash for dyn AnyReference<R> + '_
+where
+    R: ?Sized + Hash,
+{
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.target().hash(state)
+    }
+}
+
+macro_rules! call_implementation {
+    ($reference_name: ident) => {
+        call_implementation!(@arities $reference_name []);
+        call_implementation!(@arities $reference_name [+ Send]);
+        call_implementation!(@arities $reference_name [+ Send + Sync]);
+    };
+    (@arities $reference_name: ident [$($bounds: tt)*]) => {
+        call_implementation!(@impl $reference_name [$($bounds)*]);
+        call_implementation!(@impl $reference_name [$($bounds)*] a A);
+        call_implementation!(@impl $reference_name [$($bounds)*] a A, b B);
+        call_implementation!(@impl $reference_name [$($bounds)*] a A, b B, c C);
+        call_implementation!(@impl $reference_name [$($bounds)*] a A, b B, c C, d D);
+    };
+    (@impl $reference_name: ident [$($bounds: tt)*] $($argument: ident $A: ident),*) => {
+        impl<O, Ret, $($A),*> $reference_name<O, dyn Fn($($A),*) -> Ret $($bounds)*>
+        where
+            O: ?Sized,
+        {
+            /// Calls the referenced closure.
+            pub fn call(&self, $($argument: $A),*) -> Ret {
+                (**self)($($argument),*)
+            }
+        }
+    };
+}
+
+macro_rules! downcast_target_implementation {
+    ($reference_name: ident, $any: ty) => {
+        impl<O> $reference_name<O, $any>
+        where
+            O: ?Sized,
+        {
+            /// Narrows the target to `T` if that is its concrete type.
+            pub fn downcast_target<T: Any>(self) -> Result<$reference_name<O, T>, Self> {
+                match self.downcast_ref::<T>() {
+                    Some(target) => {
+                        let ptr = NonNull::from(target);
+                        Ok($reference_name::from_parts(self.inner, ptr))
+                    }
+                    None => Err(self),
+                }
+            }
+        }
+    };
+}
+
+macro_rules! split_tuple_implementation {
+    ($reference_name: ident) => {
+        split_tuple_implementation!(
+            $reference_name,
+            (A 0),
+            (A 0, B 1),
+            (A 0, B 1, C 2),
+            (A 0, B 1, C 2, D 3),
+            (A 0, B 1, C 2, D 3, E 4),
+            (A 0, B 1, C 2, D 3, E 4, F 5),
+            (A 0, B 1, C 2, D 3, E 4, F 5, G 6),
+            (A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7),
+            (A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8),
+            (A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9),
+            (A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9, K 10),
+            (A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9, K 10, L 11),
+        );
+    };
+    ($reference_name: ident, $(($($T: ident $index: tt),+)),+ $(,)?) => {
+        $(
+            impl<O, $($T),+> $reference_name<O, ($($T,)+)>
+            where
+                O: ?Sized,
+            {
+                pub fn split_tuple(self) -> ($($reference_name<O, $T>,)+) {
+                    ($(
+                        $reference_name::from_parts(
+                            self.inner.clone(),
+                            NonNull::from(&(*self).$index),
+                        ),
+                    )+)
+                }
+            }
+        )+
+    };
+}
+
+implementation!(
+    RcReference,
+    RcWeakReference,
+    RcMultipleContext,
+    RcProjector,
+    Rc,
+    rc,
+    (),
+    rc_multiple,
+    rc_field
+);
+implementation!(
+    ArcReference,
+    ArcWeakReference,
+    ArcMultipleContext,
+    ArcProjector,
+    Arc,
+    sync,
+    ArcMetrics,
+    arc_multiple,
+    arc_field
+);
+
+unsafe impl<O, R> Send for ArcReference<O, R>
+where
+    O: ?Sized,
+    R: ?Sized,
+    Arc<O>: Send,
+    for<'r> &'r R: Send,
+{
+}
+
+unsafe impl<O, R> Sync for ArcReference<O, R>
+where
+    O: ?Sized,
+    R: ?Sized,
+    Arc<O>: Sync,
+    for<'r> &'r R: Sync,
+{
+}
+
+unsafe impl<O, R> Send for ArcWeakReference<O, R>
+where
+    O: ?Sized,
+    R: ?Sized,
+    Arc<O>: Send,
+    for<'r> &'r R: Send,
+{
+}
+
+unsafe impl<O, R> Sync for ArcWeakReference<O, R>
+where
+    O: ?Sized,
+    R: ?Sized,
+    Arc<O>: Sync,
+    for<'r> &'r R: Sync,
+{
+}
+
+impl<O, R> ArcReference<O, R>
+where
+    O: ?Sized,
+    R: ?Sized,
+{
+    /// Erases the reference's type, leaving only access to its target.
+    pub fn into_opaque(self) -> Box<dyn Deref<Target = R> + Send + Sync>
+    where
+        Self: Send + Sync + 'static,
+    {
+        Box::new(self)
+    }
+}
+
+/// An `ArcReference` with its owner type erased, see `ArcReference::erase`.
+pub struct ErasedArcReference<R>
+where
+    R: ?Sized,
+{
+    inner: Box<dyn Deref<Target = R> + Send + Sync>,
+}
+
+impl<O, R> ArcReference<O, R>
+where
+    O: ?Sized,
+    R: ?Sized,
+{
+    /// Erases the owner type, so references into different owners can share a type.
+    pub fn erase(self) -> ErasedArcReference<R>
+    where
+        Self: Send + Sync + 'static,
+    {
+        ErasedArcReference {
+            inner: self.into_opaque(),
+        }
+    }
+}
+
+impl<R> Deref for ErasedArcReference<R>
+where
+    R: ?Sized,
+{
+    type Target = R;
+
+    fn deref(&self) -> &R {
+        &self.inner
+    }
+}
+
+impl<R> Debug for ErasedArcReference<R>
+where
+    R: ?Sized + Debug,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        <R as Debug>::fmt(self, f)
+    }
+}
+
+impl<R> PartialEq for ErasedArcReference<R>
+where
+    R: ?Sized + PartialEq,
+{
+    fn eq(&self, other: &Self) -> bool {
+        **self == **other
+    }
+}
+
+impl<R> Eq for ErasedArcReference<R> where R: ?Sized + Eq {}
+
+impl<R> PartialOrd for ErasedArcReference<R>
+where
+    R: ?Sized + PartialOrd,
+{
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        (**self).partial_cmp(&**other)
+    }
+}
+
+impl<R> Ord for ErasedArcReference<R>
+where
+    R: ?Sized + Ord,
+{
+    fn cmp(&self, other: &Self) -> Ordering {
+        (**self).cmp(&**other)
+    }
+}
+
+impl<R> Hash for ErasedArcReference<R>
+where
+    R: ?Sized + Hash,
+{
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        (**self).hash(state)
+    }
+}
+
+impl<R> Borrow<R> for ErasedArcReference<R>
+where
+    R: ?Sized,
+{
+    fn borrow(&self) -> &R {
+        self
+    }
+}
+
+impl<O, T> RcReference<O, Cell<T>>
+where
+    O: ?Sized,
+{
+    pub fn get(&self) -> T
+    where
+        T: Copy,
+    {
+        (**self).get()
+    }
+
+    pub fn set(&self, value: T) {
+        (**self).set(value)
+    }
+
+    pub fn replace(&self, value: T) -> T {
+        (**self).replace(value)
+    }
+}
+
+impl<O> ArcReference<O, AtomicUsize>
+where
+    O: ?Sized,
+{
+    pub fn load(&self, order: atomic::Ordering) -> usize {
+        (**self).load(order)
+    }
+
+    pub fn store(&self, value: usize, order: atomic::Ordering) {
+        (**self).store(value, order)
+    }
+
+    pub fn fetch_add(&self, value: usize, order: atomic::Ordering) -> usize {
+        (**self).fetch_add(value, order)
+    }
+}
+
+/// Creates an independent reference to every element of a shared `Vec<T>` or `[T]`,
+/// incrementing the owner's strong count once per element.
+pub fn references_for_each<O, T>(arc: &Arc<O>) -> Vec<ArcReference<O, T>>
+where
+    O: ?Sized + AsRef<[T]>,
+{
+    (**arc)
+        .as_ref()
+        .iter()
+        .map(|element| ArcReference::from_parts(arc.clone(), NonNull::from(element)))
+        .collect()
+}
+
+/// Byte buffers shared with APIs that take a `&[u8]`, such as zero-copy parsers.
+///
+/// Slices parsed out of [`as_read_only_slice`](Self::as_read_only_slice) borrow from the
+/// reference, so they can't outlive it; the reference in turn keeps the buffer alive.
+impl<O> ArcReference<O, [u8]>
+where
+    O: ?Sized,
+{
+    pub fn as_read_only_slice(&self) -> &[u8] {
+        self
+    }
+
+    /// Validates the bytes as UTF-8 and borrows them as a `&str` without copying.
+    pub fn as_str_checked(&self) -> Result<&str, Utf8Error> {
+        std::str::from_utf8(self)
+    }
+
+    /// Returns the reference together with a raw pointer to its bytes.
+    ///
+    /// The pointer stays valid for as long as the returned reference, or any clone of it,
+    /// is alive.
+    pub fn into_owned_and_slice(self) -> (Self, *const [u8]) {
+        let slice = self.ptr.as_ptr() as *const [u8];
+        (self, slice)
+    }
+
+    /// Formats the bytes like `hexdump -C`: an offset, sixteen bytes in hex and the same
+    /// bytes as ASCII per row.
+    pub fn hex_dump(&self) -> impl Debug + '_ {
+        HexDump(self)
+    }
+
+    /// Splits the bytes into records, each keeping the buffer alive.
+    ///
+    /// `parse` is given the bytes that have not been consumed yet and returns how many of them
+    /// the next record takes up, together with the record's range within them. Iteration
+    /// stops once `parse` returns `None`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `parse` returns a length or range past the end of the bytes it was given.
+    pub fn records<'a>(
+        &'a self,
+        mut parse: impl FnMut(&[u8]) -> Option<(usize, Range<usize>)> + 'a,
+    ) -> impl Iterator<Item = ArcReference<O, [u8]>> + 'a {
+        let mut rest: &[u8] = self;
+        std::iter::from_fn(move || {
+            let (consumed, range) = parse(rest)?;
+            let record = NonNull::from(&rest[range]);
+            rest = &rest[consumed..];
+            Some(ArcReference::from_parts(self.inner.clone(), record))
+        })
+    }
+}
+
+impl<O> ArcReference<O, str>
+where
+    O: ?Sized,
+{
+    /// Splits off the first `byte_len` bytes, returning them and the remainder.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `byte_len` is not on a `char` boundary or past the end of the string.
+    pub fn take_prefix_str(self, byte_len: usize) -> (ArcReference<O, str>, ArcReference<O, str>) {
+        let (prefix, rest) = self.split_at(byte_len);
+        let (prefix, rest) = (NonNull::from(prefix), NonNull::from(rest));
+
+        (
+            ArcReference::from_parts(self.inner.clone(), prefix),
+            ArcReference::from_parts(self.inner, rest),
+        )
+    }
+}
+
+struct HexDump<'a>(&'a [u8]);
+
+impl Debug for HexDump<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (row, chunk) in self.0.chunks(16).enumerate() {
+            if row != 0 {
+                writeln!(f)?;
+            }
+
+            write!(f, "{:08x}  ", row * 16)?;
+
+            for i in 0..16 {
+                if i == 8 {
+                    write!(f, " ")?;
+                }
+
+                match chunk.get(i) {
+                    Some(byte) => write!(f, "{byte:02x} ")?,
+                    None => write!(f, "   ")?,
+                }
+            }
+
+            write!(f, " |")?;
+            for &byte in chunk {
+                let c = if byte.is_ascii_graphic() || byte == b' ' {
+                    byte as char
+                } else {
+                    '.'
+                };
+                write!(f, "{c}")?;
+            }
+            write!(f, "|")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Nodes of an adjacency list stored as a slice, referring to each other by index.
+pub trait GraphNode {
+    fn edges(&self) -> &[usize];
+}
+
+impl<O, N> ArcReference<O, [N]>
+where
+    O: ?Sized,
+    N: GraphNode,
+{
+    /// Follows the `edge`th edge of `node`, which should be an element of this slice.
+    pub fn neighbor(&self, node: &ArcReference<O, N>, edge: usize) -> Option<ArcReference<O, N>> {
+        self.element(*node.edges().get(edge)?)
+    }
+}
+
+/// Owners that may already be an `Arc<[T]>`, allowing `into_arc_slice` to reuse them.
+pub trait SliceOwner<T> {
+    fn as_arc_slice(owner: &Arc<Self>) -> Option<Arc<[T]>>;
+}
+
+impl<T> SliceOwner<T> for [T] {
+    fn as_arc_slice(owner: &Arc<Self>) -> Option<Arc<[T]>> {
+        Some(owner.clone())
+    }
+}
+
+impl<T> SliceOwner<T> for Vec<T> {
+    fn as_arc_slice(_owner: &Arc<Self>) -> Option<Arc<[T]>> {
+        None
+    }
+}
+
+impl<O, T> ArcReference<O, [T]>
+where
+    O: ?Sized,
+{
+    pub fn element(&self, index: usize) -> Option<ArcReference<O, T>> {
+        Some(ArcReference::from_parts(
+            self.inner.clone(),
+            NonNull::from(self.get(index)?),
+        ))
+    }
+
+    /// Splits off the first `n` elements, returning them and the remainder.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n` is larger than the length of the slice.
+    pub fn take_prefix(self, n: usize) -> (ArcReference<O, [T]>, ArcReference<O, [T]>) {
+        let (prefix, rest) = self.split_at(n);
+        let (prefix, rest) = (NonNull::from(prefix), NonNull::from(rest));
+
+        (
+            ArcReference::from_parts(self.inner.clone(), prefix),
+            ArcReference::from_parts(self.inner, rest),
+        )
+    }
+
+    /// Joins two references into one covering both, if `b` starts exactly where `a` ends
+    /// and both lie inside the slice `whole` projects from their shared owner. This
+    /// reverses `take_prefix`.
+    ///
+    /// The merged reference is a subslice of `whole`, so ranges that merely happen to be
+    /// adjacent in memory, but belong to different allocations, are never merged.
+    pub fn merge_adjacent(a: Self, b: Self, whole: impl FnOnce(&O) -> &[T]) -> Option<Self> {
+        if !Arc::ptr_eq(&a.inner, &b.inner) || a.as_ptr_range().end != b.as_ptr() {
+            return None;
+        }
+
+        let whole = whole(&a.inner);
+        let (start, end) = (whole.as_ptr_range().start, whole.as_ptr_range().end);
+        if a.as_ptr() < start || b.as_ptr_range().end > end {
+            return None;
+        }
+
+        let offset = match std::mem::size_of::<T>() {
+            0 => 0,
+            size => (a.as_ptr() as usize - start as usize) / size,
+        };
+        let merged = NonNull::from(&whole[offset..offset + a.len() + b.len()]);
+
+        Some(ArcReference::from_parts(a.inner, merged))
+    }
+
+    pub fn explode(self) -> Arc<[ArcReference<O, T>]> {
+        self.iter()
+            .map(|element| ArcReference::from_parts(self.inner.clone(), NonNull::from(element)))
+            .collect()
+    }
+
+    /// Converts the target into an `Arc<[T]>`, reusing the owner if it is an `Arc<[T]>`
+    /// and the target covers all of it, and copying the target otherwise.
+    pub fn into_arc_slice(self) -> Arc<[T]>
+    where
+        O: SliceOwner<T>,
+        T: Clone,
+    {
+        match O::as_arc_slice(&self.inner) {
+            Some(slice) if std::ptr::eq(&*slice, &*self) => slice,
+            _ => Arc::from(&*self),
+        }
+    }
+}
+
+/// Orders references by identity rather than by value: first by the address of the
+/// owner's allocation and then by the target pointer (including its metadata).
+///
+/// The order is total and stable for the lifetime of the compared owners, which makes
+/// it usable as a `BTreeMap` key even if `R` is not `Ord`. It says nothing about the
+/// targets' values, and is not consistent across runs.
+pub struct OrderedByOwner<O, R>(pub ArcReference<O, R>)
+where
+    O: ?Sized,
+    R: ?Sized;
+
+impl<O, R> OrderedByOwner<O, R>
+where
+    O: ?Sized,
+    R: ?Sized,
+{
+    fn key(&self) -> (*const (), NonNull<R>) {
+        (Arc::as_ptr(&self.0.inner).cast(), self.0.ptr)
+    }
+}
+
+impl<O, R> Clone for OrderedByOwner<O, R>
+where
+    O: ?Sized,
+    R: ?Sized,
+{
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<O, R> Debug for OrderedByOwner<O, R>
+where
+    O: ?Sized,
+    R: ?Sized + Debug,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("OrderedByOwner").field(&self.0).finish()
+    }
+}
+
+impl<O, R> PartialEq for OrderedByOwner<O, R>
+where
+    O: ?Sized,
+    R: ?Sized,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<O, R> Eq for OrderedByOwner<O, R>
+where
+    O: ?Sized,
+    R: ?Sized,
+{
+}
+
+impl<O, R> PartialOrd for OrderedByOwner<O, R>
+where
+    O: ?Sized,
+    R: ?Sized,
+{
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<O, R> Ord for OrderedByOwner<O, R>
+where
+    O: ?Sized,
+    R: ?Sized,
+{
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.key().cmp(&other.key())
+    }
+}
+
+/// Mints [`ArcReference`]s from a batch of pre-acquired strong counts.
+///
+/// Instead of incrementing the owner's strong count once per reference, the pool
+/// acquires `batch` counts at a time and hands them out without any atomic operation.
+/// Counts that were acquired but not handed out are released when the pool is dropped.
+///
+/// This moves the increments out of the hot path rather than removing them: refilling
+/// still performs `batch` increments, just back to back. Dropping a minted reference
+/// decrements the count as usual. Until the pool is dropped the owner's strong count
+/// overstates the number of live references by up to `batch`, so `Arc::get_mut` and
+/// similar uniqueness checks will fail while a pool is alive.
+pub struct ArcReferencePool<O> {
+    inner: Arc<O>,
+    credits: Cell<usize>,
+    batch: usize,
+}
+
+impl<O> ArcReferencePool<O> {
+    pub fn new(inner: Arc<O>, batch: usize) -> Self {
+        Self {
+            inner,
+            credits: Cell::new(0),
+            batch: batch.max(1),
+        }
+    }
+
+    pub fn source(&self) -> &Arc<O> {
+        &self.inner
+    }
+
+    pub fn acquire<R: ?Sized>(&self, f: impl FnOnce(&O) -> &R) -> ArcReference<O, R> {
+        let ptr = Arc::as_ptr(&self.inner);
+
+        if self.credits.get() == 0 {
+            for _ in 0..self.batch {
+                unsafe { Arc::increment_strong_count(ptr) };
+            }
+            self.credits.set(self.batch);
+        }
+
+        self.credits.set(self.credits.get() - 1);
+
+        ArcReference::new(unsafe { Arc::from_raw(ptr) }, f)
+    }
+}
+
+impl<O> Drop for ArcReferencePool<O> {
+    fn drop(&mut self) {
+        let ptr = Arc::as_ptr(&self.inner);
+
+        for _ in 0..self.credits.get() {
+            unsafe { Arc::decrement_strong_count(ptr) };
+        }
+    }
+}
+
+/// A slot holding an optional [`ArcReference`] that can be filled once by any thread.
+///
+/// Through a shared reference a value can only be installed into an empty slot, never
+/// replaced or removed, so readers never observe a reference being freed under them.
+/// Replacing or taking the value requires exclusive access.
+pub struct AtomicOptionArcReference<O, R>
+where
+    O: ?Sized,
+    R: ?Sized,
+{
+    slot: AtomicPtr<ArcReference<O, R>>,
+    _marker: PhantomData<*const ArcReference<O, R>>,
+}
+
+unsafe impl<O, R> Send for AtomicOptionArcReference<O, R>
+where
+    O: ?Sized,
+    R: ?Sized,
+    ArcReference<O, R>: Send,
+{
+}
+
+unsafe impl<O, R> Sync for AtomicOptionArcReference<O, R>
+where
+    O: ?Sized,
+    R: ?Sized,
+    ArcReference<O, R>: Send + Sync,
+{
+}
+
+impl<O, R> AtomicOptionArcReference<O, R>
+where
+    O: ?Sized,
+    R: ?Sized,
+{
+    pub fn new(value: Option<ArcReference<O, R>>) -> Self {
+        Self {
+            slot: AtomicPtr::new(Self::into_ptr(value)),
+            _marker: PhantomData,
+        }
+    }
+
+    fn into_ptr(value: Option<ArcReference<O, R>>) -> *mut ArcReference<O, R> {
+        value.map_or(std::ptr::null_mut(), |value| Box::into_raw(Box::new(value)))
+    }
+
+    pub fn get(&self) -> Option<ArcReference<O, R>> {
+        let ptr = self.slot.load(atomic::Ordering::Acquire);
+        unsafe { ptr.as_ref() }.cloned()
+    }
+
+    /// Returns the installed reference, installing the one returned by `f` if the slot is
+    /// empty.
+    ///
+    /// If several threads race to fill an empty slot, each of them may call `f`, but only
+    /// one result is installed and every caller receives that one.
+    pub fn get_or_init(&self, f: impl FnOnce() -> ArcReference<O, R>) -> ArcReference<O, R> {
+        if let Some(value) = self.get() {
+            return value;
+        }
+
+        let new = Box::into_raw(Box::new(f()));
+
+        match self.slot.compare_exchange(
+            std::ptr::null_mut(),
+            new,
+            atomic::Ordering::AcqRel,
+            atomic::Ordering::Acquire,
+        ) {
+            Ok(_) => unsafe { (*new).clone() },
+            Err(installed) => {
+                drop(unsafe { Box::from_raw(new) });
+                unsafe { (*installed).clone() }
+            }
+        }
+    }
+
+    pub fn replace(&mut self, value: Option<ArcReference<O, R>>) -> Option<ArcReference<O, R>> {
+        let old = std::mem::replace(self.slot.get_mut(), Self::into_ptr(value));
+        (!old.is_null()).then(|| *unsafe { Box::from_raw(old) })
+    }
+
+    pub fn take(&mut self) -> Option<ArcReference<O, R>> {
+        self.replace(None)
+    }
+
+    pub fn into_inner(mut self) -> Option<ArcReference<O, R>> {
+        self.take()
+    }
+}
+
+impl<O, R> Default for AtomicOptionArcReference<O, R>
+where
+    O: ?Sized,
+    R: ?Sized,
+{
+    fn default() -> Self {
+        Self::new(None)
+    }
+}
+
+impl<O, R> Drop for AtomicOptionArcReference<O, R>
+where
+    O: ?Sized,
+    R: ?Sized,
+{
+    fn drop(&mut self) {
+        self.take();
+    }
+}
+
+/// A shared, replaceable [`ArcReference`], for publishing new versions of a value
+/// (e.g. a reloaded configuration) to concurrent readers.
+///
+/// This is lock-based, not lock-free: the current reference sits behind an `RwLock`.
+/// Readers only take the read lock to clone it, so they don't block each other, and the
+/// write lock is only held to swap it, never while user code runs. `update` computes the
+/// new version without holding the lock and retries if another update was published in
+/// the meantime.
+pub struct AtomicArcReference<O, R>
+where
+    O: ?Sized,
+    R: ?Sized,
+{
+    current: RwLock<ArcReference<O, R>>,
+}
+
+impl<O, R> AtomicArcReference<O, R>
+where
+    O: ?Sized,
+    R: ?Sized,
+{
+    pub fn new(reference: ArcReference<O, R>) -> Self {
+        Self {
+            current: RwLock::new(reference),
+        }
+    }
+
+    pub fn load(&self) -> ArcReference<O, R> {
+        self.current.read().unwrap().clone()
+    }
+
+    pub fn store(&self, reference: ArcReference<O, R>) {
+        drop(self.swap(reference));
+    }
+
+    pub fn swap(&self, reference: ArcReference<O, R>) -> ArcReference<O, R> {
+        std::mem::replace(&mut *self.current.write().unwrap(), reference)
+    }
+
+    /// Builds a new owner from the current target with `f`, projects into it with
+    /// `project` and publishes the result, returning it.
+    ///
+    /// `f` and `project` may run more than once if other updates race with this one;
+    /// only a version built from the latest published target is ever stored.
+    pub fn update(&self, f: impl Fn(&R) -> O, project: impl Fn(&O) -> &R) -> ArcReference<O, R>
+    where
+        O: Sized,
+    {
+        loop {
+            let current = self.load();
+            let new = ArcReference::new(Arc::new(f(&current)), &project);
+
+            let mut guard = self.current.write().unwrap();
+            if Arc::ptr_eq(&guard.inner, &current.inner) && std::ptr::eq(&**guard, &*current) {
+                *guard = new.clone();
+                return new;
+            }
+        }
+    }
+
+    pub fn into_inner(self) -> ArcReference<O, R> {
+        self.current.into_inner().unwrap()
+    }
+}
+
+/// Hands out references that borrow a single shared owner instead of each holding a
+/// strong count of their own.
+///
+/// [`GroupedReference`]s can't outlive the group they were created from; the owner's
+/// strong count is only released once, when the group is dropped. Use
+/// [`GroupedReference::to_reference`] to turn one into an independent [`ArcReference`].
+pub struct ReferenceGroup<O>
+where
+    O: ?Sized,
+{
+    inner: Arc<O>,
+}
+
+impl<O> ReferenceGroup<O>
+where
+    O: ?Sized,
+{
+    pub fn new(inner: Arc<O>) -> Self {
+        Self { inner }
+    }
+
+    pub fn source(&self) -> &Arc<O> {
+        &self.inner
+    }
+
+    pub fn get<R: ?Sized>(&self, f: impl FnOnce(&O) -> &R) -> GroupedReference<'_, O, R> {
+        GroupedReference {
+            inner: &self.inner,
+            target: f(&self.inner),
+        }
+    }
+}
+
+pub struct GroupedReference<'a, O, R>
+where
+    O: ?Sized,
+    R: ?Sized,
+{
+    inner: &'a Arc<O>,
+    target: &'a R,
+}
+
+impl<'a, O, R> GroupedReference<'a, O, R>
+where
+    O: ?Sized,
+    R: ?Sized,
+{
+    pub fn source(&self) -> &'a Arc<O> {
+        self.inner
+    }
+
+    pub fn to_reference(&self) -> ArcReference<O, R> {
+        ArcReference::from_parts(self.inner.clone(), NonNull::from(self.target))
+    }
+}
+
+impl<O, R> Clone for GroupedReference<'_, O, R>
+where
+    O: ?Sized,
+    R: ?Sized,
+{
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<O, R> Copy for GroupedReference<'_, O, R>
+where
+    O: ?Sized,
+    R: ?Sized,
+{
+}
+
+impl<O, R> Deref for GroupedReference<'_, O, R>
+where
+    O: ?Sized,
+    R: ?Sized,
+{
+    type Target = R;
+
+    fn deref(&self) -> &Self::Target {
+        self.target
+    }
+}
+
+/// Many targets within one shared owner, stored as plain pointers next to a single
+/// `Arc` and addressed by index.
+///
+/// Holding `n` targets this way costs one strong count and one pointer per target,
+/// instead of `n` strong counts and an `Arc` per target for `n` [`ArcReference`]s.
+pub struct SharedReferenceArena<O, R>
+where
+    O: ?Sized,
+    R: ?Sized,
+{
+    inner: Arc<O>,
+    targets: Vec<NonNull<R>>,
+}
+
+unsafe impl<O, R> Send for SharedReferenceArena<O, R>
+where
+    O: ?Sized,
+    R: ?Sized,
+    ArcReference<O, R>: Send,
+{
+}
+
+unsafe impl<O, R> Sync for SharedReferenceArena<O, R>
+where
+    O: ?Sized,
+    R: ?Sized,
+    ArcReference<O, R>: Sync,
+{
+}
+
+impl<O, R> SharedReferenceArena<O, R>
+where
+    O: ?Sized,
+    R: ?Sized,
+{
+    pub fn new(inner: Arc<O>) -> Self {
+        Self {
+            inner,
+            targets: Vec::new(),
+        }
+    }
+
+    /// Moves references that all share one owner into an arena, keeping their order.
+    ///
+    /// Returns the references back if they are empty or don't all share the same owner.
+    pub fn coalesce(references: Vec<ArcReference<O, R>>) -> Result<Self, Vec<ArcReference<O, R>>> {
+        let Some(first) = references.first() else {
+            return Err(references);
+        };
+
+        if !references
+            .iter()
+            .all(|reference| Arc::ptr_eq(&reference.inner, &first.inner))
+        {
+            return Err(references);
+        }
+
+        let inner = first.inner.clone();
+        let targets = references.iter().map(|reference| reference.ptr).collect();
+
+        Ok(Self { inner, targets })
+    }
+
+    pub fn source(&self) -> &Arc<O> {
+        &self.inner
+    }
+
+    pub fn push(&mut self, f: impl FnOnce(&O) -> &R) -> usize {
+        self.targets.push(NonNull::from(f(&self.inner)));
+        self.targets.len() - 1
+    }
+
+    pub fn get(&self, index: usize) -> Option<&R> {
+        let ptr = self.targets.get(index)?;
+        Some(unsafe { &*ptr.as_ptr() })
+    }
+
+    /// Creates an independent reference to the target at `index`.
+    pub fn reference(&self, index: usize) -> Option<ArcReference<O, R>> {
+        let ptr = *self.targets.get(index)?;
+        Some(ArcReference::from_parts(self.inner.clone(), ptr))
+    }
+
+    pub fn len(&self) -> usize {
+        self.targets.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.targets.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &R> + '_ {
+        self.targets.iter().map(|ptr| unsafe { &*ptr.as_ptr() })
+    }
+}
+
+/// A growable store of entries in a shared `Vec`, handing out references that keep
+/// the entries alive independently of the slab.
+///
+/// Inserting while references are outstanding copies the entries into a new `Vec`
+/// first (see `Arc::make_mut`), so existing references keep seeing the old one.
+pub struct ArcSlab<T> {
+    entries: Arc<Vec<T>>,
+}
+
+impl<T> ArcSlab<T> {
+    pub fn new() -> Self {
+        Self {
+            entries: Arc::new(Vec::new()),
+        }
+    }
+
+    pub fn insert(&mut self, value: T) -> usize
+    where
+        T: Clone,
+    {
+        let entries = Arc::make_mut(&mut self.entries);
+        entries.push(value);
+        entries.len() - 1
+    }
+
+    pub fn get(&self, key: usize) -> Option<ArcReference<Vec<T>, T>> {
+        let ptr = NonNull::from(self.entries.get(key)?);
+        Some(ArcReference::from_parts(self.entries.clone(), ptr))
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+impl<T> Default for ArcSlab<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Interns [`ArcReference`]s by target value without keeping them alive.
+///
+/// Entries are stored as [`ArcWeakReference`]s grouped by the hash of their target.
+/// Expired entries are pruned from a hash bucket whenever it is accessed, and a full
+/// sweep runs once the number of stored entries has doubled since the last one, so
+/// entries whose value is never looked up again don't accumulate.
+pub struct WeakArcReferenceSet<O, R>
+where
+    O: ?Sized,
+    R: ?Sized,
+{
+    state: Mutex<WeakSetState<O, R>>,
+    hasher: RandomState,
+}
+
+struct WeakSetState<O, R>
+where
+    O: ?Sized,
+    R: ?Sized,
+{
+    buckets: HashMap<u64, Vec<ArcWeakReference<O, R>>>,
+    len: usize,
+    sweep_at: usize,
+}
+
+impl<O, R> WeakSetState<O, R>
+where
+    O: ?Sized,
+    R: ?Sized,
+{
+    const MIN_SWEEP: usize = 16;
+
+    fn sweep(&mut self) {
+        self.buckets.retain(|_, bucket| {
+            bucket.retain(|weak| weak.strong_count() > 0);
+            !bucket.is_empty()
+        });
+
+        self.len = self.buckets.values().map(Vec::len).sum();
+        self.sweep_at = (self.len * 2).max(Self::MIN_SWEEP);
+    }
+}
+
+impl<O, R> WeakArcReferenceSet<O, R>
+where
+    O: ?Sized,
+    R: ?Sized + Hash + Eq,
+{
+    pub fn new() -> Self {
+        Self {
+            state: Mutex::new(WeakSetState {
+                buckets: HashMap::new(),
+                len: 0,
+                sweep_at: WeakSetState::<O, R>::MIN_SWEEP,
+            }),
+            hasher: RandomState::new(),
+        }
+    }
+
+    pub fn get(&self, key: &R) -> Option<ArcReference<O, R>> {
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        Self::find(&mut state, self.hasher.hash_one(key), key)
+    }
+
+    /// Returns the live reference whose target equals `key`, or inserts the one returned
+    /// by `f`.
+    ///
+    /// `f` is called while the set is locked, so concurrent callers interning the same
+    /// value all receive the reference created by a single call to `f`. If `f` panics the
+    /// set is left unchanged and stays usable.
+    pub fn get_or_insert_with(
+        &self,
+        key: &R,
+        f: impl FnOnce() -> ArcReference<O, R>,
+    ) -> ArcReference<O, R> {
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+
+        if let Some(reference) = Self::find(&mut state, self.hasher.hash_one(key), key) {
+            return reference;
+        }
+
+        let reference = f();
+
+        state
+            .buckets
+            .entry(self.hasher.hash_one(&*reference))
+            .or_default()
+            .push(reference.downgrade());
+
+        state.len += 1;
+        if state.len >= state.sweep_at {
+            state.sweep();
+        }
+
+        reference
+    }
+
+    fn find(state: &mut WeakSetState<O, R>, hash: u64, key: &R) -> Option<ArcReference<O, R>> {
+        let bucket = state.buckets.get_mut(&hash)?;
+
+        let before = bucket.len();
+        let mut found = None;
+        bucket.retain(|weak| match weak.upgrade() {
+            Some(reference) => {
+                if found.is_none() && *reference == *key {
+                    found = Some(reference);
+                }
+                true
+            }
+            None => false,
+        });
+
+        let removed = before - bucket.len();
+        if bucket.is_empty() {
+            state.buckets.remove(&hash);
+        }
+        state.len -= removed;
+
+        found
+    }
+}
+
+impl<O, R> Default for WeakArcReferenceSet<O, R>
+where
+    O: ?Sized,
+    R: ?Sized + Hash + Eq,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Creates a reference by descending through a chain of field accesses, indexing and
+/// dereferences, written as a path starting at the owner.
+///
+/// `.field` and `.0` access fields, `[index]` indexes and `.*` dereferences once (so
+/// `.**` dereferences twice). The reference type defaults to [`ArcReference`] and can be
+/// given explicitly, e.g. `project_through!(RcReference, owner => .field)`.
+///
+/// ```
+/// use std::sync::Arc;
+/// use reference_arc::project_through;
+///
+/// struct Server {
+///     host: String,
+/// }
+///
+/// struct Config {
+///     servers: Vec<Server>,
+/// }
+///
+/// let config = Arc::new(Box::new(Config {
+///     servers: vec![Server {
+///         host: String::from("localhost"),
+///     }],
+/// }));
+///
+/// let host = project_through!(config => .**.servers[0].host);
+/// assert_eq!(*host, "localhost");
+/// ```
+#[macro_export]
+macro_rules! project_through {
+    (@path ($($expr: tt)*)) => {
+        $($expr)*
+    };
+    (@path ($($expr: tt)*) . * $($rest: tt)*) => {
+        $crate::project_through!(@path (*($($expr)*)) $($rest)*)
+    };
+    (@path ($($expr: tt)*) * $($rest: tt)*) => {
+        $crate::project_through!(@path (*($($expr)*)) $($rest)*)
+    };
+    (@path ($($expr: tt)*) . $field: ident $($rest: tt)*) => {
+        $crate::project_through!(@path (($($expr)*).$field) $($rest)*)
+    };
+    (@path ($($expr: tt)*) . $index: tt $($rest: tt)*) => {
+        $crate::project_through!(@path (($($expr)*).$index) $($rest)*)
+    };
+    (@path ($($expr: tt)*) [$($index: tt)*] $($rest: tt)*) => {
+        $crate::project_through!(@path (($($expr)*)[$($index)*]) $($rest)*)
+    };
+    ($reference: ident, $owner: expr => $($path: tt)+) => {
+        $crate::$reference::new($owner, |owner| {
+            &$crate::project_through!(@path (owner) $($path)+)
+        })
+    };
+    ($owner: expr => $($path: tt)+) => {
+        $crate::project_through!(ArcReference, $owner => $($path)+)
+    };
+}
+
+/// Projects several fields of an `Arc` owner into a map from field name to an erased
+/// `dyn Any` reference, cloning the owner once per field.
+///
+/// ```
+/// use std::sync::Arc;
+///
+/// use reference_arc::project_map;
+///
+/// struct Server {
+///     host: String,
+///     port: u16,
+/// }
+///
+/// let server = Arc::new(Server {
+///     host: String::from("localhost"),
+///     port: 8080,
+/// });
+///
+/// let fields = project_map!(server => host, port);
+/// assert_eq!(fields["port"].downcast_ref::<u16>(), Some(&8080));
+/// ```
+#[macro_export]
+macro_rules! project_map {
+    ($owner: expr => $($field: ident),+ $(,)?) => {{
+        let owner = &$owner;
+        let mut map = ::std::collections::HashMap::<
+            &'static str,
+            $crate::ErasedArcReference<dyn ::std::any::Any + Send + Sync>,
+        >::new();
+        $(
+            map.insert(
+                stringify!($field),
+                $crate::ArcReference::new(::std::sync::Arc::clone(owner), |owner| {
+                    &owner.$field as &(dyn ::std::any::Any + Send + Sync)
+                })
+                .erase(),
+            );
+        )+
+        map
+    }};
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Barrier;
+
+    use super::*;
+
+    #[test]
+    fn rc() {
+        let rc = Rc::new(String::from("Hello World!"));
+
+        let hello = RcReference::new(rc.clone(), |string| &string[0..5]);
+        let world = RcReference::new(rc.clone(), |string| &string[6..11]);
+
+        assert_eq!(format!("{hello} {world}"), "Hello World");
+    }
+
+    #[test]
+    fn arc() {
+        let arc = Arc::new(String::from("Hello World!"));
+
+        let hello = ArcReference::new(arc.clone(), |string| &string[0..5]);
+        let world = ArcReference::new(arc.clone(), |string| &string[6..11]);
+
+        assert_eq!(format!("{hello} {world}"), "Hello World");
+    }
+
+    #[test]
+    fn drop_arc() {
+        let arc = Arc::new(String::from("Hello World!"));
+
+        let hello = ArcReference::new(arc.clone(), |string| &string[0..5]);
+        let world = ArcReference::new(arc.clone(), |string| &string[6..11]);
+
+        drop(arc);
+
+        assert_eq!(format!("{hello} {world}"), "Hello World");
+    }
+
+    #[test]
+    fn access_source() {
+        let rc = Rc::new(String::from("Hello World!"));
+
+        let hello = RcReference::new(rc.clone(), |string| &string[0..5]);
+
+        drop(rc);
+
+        let world = RcReference::new(hello.source().clone(), |string| &string[6..11]);
+
+        assert_eq!(format!("{hello} {world}"), "Hello World");
+    }
+
+    #[test]
+    fn threaded() {
+        macro_rules! with_cloned {
+            ([$($variables: ident),* $(,)?], $body: stmt) => {
+                {
+                    $(let $variables = $variables.clone();)*
+
+                    $body
+                }
+            };
+        }
+
+        let arc = Arc::new(String::from("Hello World!"));
+
+        let hello = ArcReference::new(arc.clone(), |string| &string[0..5]);
+        let world = ArcReference::new(arc.clone(), |string| &string[6..11]);
+
+        let barrier = Arc::new(Barrier::new(3));
+
+        let a = with_cloned!(
+            [barrier, hello, world],
+            std::thread::spawn(move || {
+                barrier.wait();
+                assert_eq!(format!("{hello} {world}"), "Hello World");
+            })
+        );
+
+        let b = with_cloned!(
+            [barrier, hello, world],
+            std::thread::spawn(move || {
+                barrier.wait();
+                assert_eq!(format!("{world} {hello}"), "World Hello");
+            })
+        );
+
+        let c = std::thread::spawn(move || {
+            drop(arc);
+            barrier.wait();
+        });
+
+        a.join().unwrap();
+        b.join().unwrap();
+        c.join().unwrap();
+    }
+
+    #[test]
+    fn test_multiple_arc() {
+        struct Foo {
+            a: u8,
+            b: u32,
+            c: String,
+        }
+
+        let foo = Arc::new(Foo {
+            a: 42,
+            b: 1024,
+            c: String::from("Foo"),
+        });
+
+        let (a, b, c) = arc_multiple(&foo, |ctx, value| {
+            (
+                ctx.new_reference(&value.a),
+                ctx.new_reference(&value.b),
+                ctx.new_reference(&value.c),
+            )
+        });
+
+        drop(foo);
+
+        let a = std::thread::spawn(move || {
+            assert_eq!(*a, 42);
+        });
+        let b = std::thread::spawn(move || {
+            assert_eq!(*b, 1024);
+        });
+        let c = std::thread::spawn(move || {
+            assert_eq!(*c, "Foo");
+        });
+
+        a.join().unwrap();
+        b.join().unwrap();
+        c.join().unwrap();
+    }
+
+    #[test]
+    fn test_multiple_rc() {
+        struct Foo {
+            a: u8,
+            b: u32,
+            c: String,
+        }
+
+        let foo = Rc::new(Foo {
+            a: 42,
+            b: 1024,
+            c: String::from("Foo"),
+        });
+
+        let (a, b, c) = rc_multiple(&foo, |ctx, value| {
+            (
+                ctx.new_reference(&value.a),
+                ctx.new_reference(&value.b),
+                ctx.new_reference(&value.c),
+            )
+        });
+
+        drop(foo);
+
+        assert_eq!(*a, 42);
+        assert_eq!(*b, 1024);
+        assert_eq!(*c, "Foo");
+    }
+
+    #[test]
+    fn atomic_counter() {
+        struct Foo {
+            _name: String,
+            counter: AtomicUsize,
+        }
+
+        let foo = Arc::new(Foo {
+            _name: String::from("Foo"),
+            counter: AtomicUsize::new(0),
+        });
+
+        let counter = ArcReference::new(foo.clone(), |foo| &foo.counter);
+
+        drop(foo);
+
+        let threads: Vec<_> = (0..4)
+            .map(|_| {
+                let counter = counter.clone();
+                std::thread::spawn(move || {
+                    for _ in 0..1000 {
+                        counter.fetch_add(1, atomic::Ordering::Relaxed);
+                    }
+                })
+            })
+            .collect();
+
+        for thread in threads {
+            thread.join().unwrap();
+        }
+
+        assert_eq!(counter.load(atomic::Ordering::SeqCst), 4000);
+
+        counter.store(7, atomic::Ordering::SeqCst);
+        assert_eq!(counter.load(atomic::Ordering::SeqCst), 7);
+    }
+
+    #[test]
+    fn with_mut_unique() {
+        let mut hello = ArcReference::new(Arc::new(String::from("Hello")), |string| &string[..]);
+
+        let len = hello.with_mut(
+            |string| {
+                string.push_str(" World!");
+                string.len()
+            },
+            |string| &string[6..11],
+        );
+
+        assert_eq!(len, Some(12));
+        assert_eq!(&*hello, "World");
+    }
+
+    #[test]
+    fn with_mut_panic() {
+        let arc = Arc::new(String::from("Hello"));
+        let mut hello = ArcReference::new(arc, |string| &string[..]);
+
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            hello.with_mut(
+                |string| {
+                    string.reserve(1 << 20);
+                    string.push('!');
+                    panic!("mutate failed");
+                },
+                |string| &string[1..],
+            )
+        }));
+
+        assert!(result.is_err());
+
+        let owner = hello.source().as_bytes().as_ptr_range();
+        let target = hello.as_bytes().as_ptr_range();
+        assert!(owner.start <= target.start && target.end <= owner.end);
+        assert_eq!(&*hello, "ello!");
+    }
+
+    #[test]
+    fn with_mut_shared() {
+        let rc = Rc::new(String::from("Hello"));
+        let mut hello = RcReference::new(rc.clone(), |string| &string[..]);
+
+        let result = hello.with_mut(|string| string.push_str(" World!"), |string| &string[..]);
+
+        assert_eq!(result, None);
+        assert_eq!(&*hello, "Hello");
+
+        drop(rc);
+
+        assert_eq!(
+            hello.with_mut(|string| string.push('!'), |string| &string[..]),
+            Some(())
+        );
+        assert_eq!(&*hello, "Hello!");
+    }
+
+    #[test]
+    fn split_tuple() {
+        let arc = Arc::new((42u8, 1024u32, String::from("Foo")));
+
+        let tuple = ArcReference::new(arc.clone(), |tuple| tuple);
+
+        drop(arc);
+
+        let (a, b, c) = tuple.split_tuple();
+
+        assert_eq!(Arc::strong_count(a.source()), 3);
+
+        let c = std::thread::spawn(move || {
+            assert_eq!(*c, "Foo");
+        });
+
+        assert_eq!(*a, 42);
+        assert_eq!(*b, 1024);
+
+        c.join().unwrap();
+    }
+
+    #[test]
+    fn explode() {
+        let arc = Arc::new(vec![1u32, 2, 3, 4]);
+
+        let elements = ArcReference::new(arc.clone(), |vec| &vec[..]).explode();
+
+        drop(arc);
+
+        assert_eq!(elements.len(), 4);
+
+        let threads: Vec<_> = (0..4)
+            .map(|i| {
+                let elements = elements.clone();
+                std::thread::spawn(move || {
+                    assert_eq!(*elements[i], i as u32 + 1);
+                })
+            })
+            .collect();
+
+        for thread in threads {
+            thread.join().unwrap();
+        }
+
+        assert_eq!(Arc::strong_count(elements[0].source()), 4);
+    }
+
+    #[test]
+    fn assume_init_ref() {
+        struct Slot {
+            name: &'static str,
+            value: MaybeUninit<u64>,
+        }
+
+        let mut arc = Arc::new(Slot {
+            name: "answer",
+            value: MaybeUninit::uninit(),
+        });
+
+        Arc::get_mut(&mut arc).unwrap().value.write(42);
+
+        let value = unsafe { ArcReference::assume_init_ref(arc.clone(), |slot| &slot.value) };
+
+        drop(arc);
+
+        assert_eq!(*value, 42);
+        assert_eq!(value.source().name, "answer");
+    }
+
+    #[test]
+    fn cmp_by() {
+        let arc = Arc::new([3.5f64, -1.0, 2.25]);
+
+        let mut references: Vec<_> = (0..3)
+            .map(|i| ArcReference::new(arc.clone(), |array| &array[i]))
+            .collect();
+
+        references.sort_by(|a, b| a.cmp_by(b, f64::total_cmp));
+
+        let sorted: Vec<f64> = references.iter().map(|reference| **reference).collect();
+        assert_eq!(sorted, [-1.0, 2.25, 3.5]);
+    }
+
+    #[test]
+    fn try_map_traced() {
+        struct Server {
+            host: Option<String>,
+        }
+
+        struct Config {
+            servers: Vec<Server>,
+        }
+
+        let config = Arc::new(Some(Config {
+            servers: vec![Server {
+                host: Some(String::from("localhost")),
+            }],
+        }));
+
+        let root = ArcReference::new(config, |config| config);
+
+        let host = root
+            .clone()
+            .try_map_traced("config", |config| config.as_ref())
+            .try_map_traced("server", |config| config.servers.first())
+            .try_map_traced("host", |server| server.host.as_deref());
+
+        let host = host.unwrap();
+        assert_eq!(host.path(), ["config", "server", "host"]);
+        assert_eq!(&*host.into_inner(), "localhost");
+
+        let error = root
+            .try_map_traced("config", |config| config.as_ref())
+            .try_map_traced("server", |config| config.servers.get(1))
+            .try_map_traced("host", |server| server.host.as_deref())
+            .unwrap_err();
+
+        assert_eq!(error.failed_at(), "server");
+        assert_eq!(error.path(), ["config", "server"]);
+        assert_eq!(error.skipped(), ["host"]);
+        assert_eq!(
+            error.to_string(),
+            "projection failed at `config → server` (skipped host)"
+        );
+    }
+
+    #[test]
+    fn into_arc_slice_reuse() {
+        let arc: Arc<[u32]> = Arc::from([1, 2, 3]);
+
+        let slice = ArcReference::new(arc.clone(), |slice| slice).into_arc_slice();
+        assert!(Arc::ptr_eq(&arc, &slice));
+
+        let partial = ArcReference::new(arc.clone(), |slice| &slice[1..]).into_arc_slice();
+        assert!(!Arc::ptr_eq(&arc, &partial));
+        assert_eq!(*partial, [2, 3]);
+    }
+
+    #[test]
+    fn into_arc_slice_copy() {
+        let arc = Arc::new(vec![1u32, 2, 3]);
+
+        let slice = ArcReference::new(arc.clone(), |vec| &vec[..]).into_arc_slice();
+
+        assert_eq!(*slice, [1, 2, 3]);
+        assert_eq!(Arc::strong_count(&arc), 1);
+    }
+
+    #[test]
+    fn try_unwrap_box_unique() {
+        let reference = ArcReference::new(Arc::new(String::from("Foo")), |string| string);
+
+        let boxed: Box<String> = reference.try_unwrap_box().unwrap();
+        assert_eq!(*boxed, "Foo");
+    }
+
+    #[test]
+    fn try_unwrap_box_other_target() {
+        static OTHER: String = String::new();
+
+        let arc = Arc::new(String::from("owner"));
+        let reference = ArcReference::new(arc, |_| &OTHER);
+
+        let reference = reference.try_unwrap_box().unwrap_err();
+        assert_eq!(*reference, "");
+        assert_eq!(Arc::strong_count(reference.source()), 1);
+    }
+
+    #[test]
+    fn try_unwrap_box_shared() {
+        let rc = Rc::new(String::from("Foo"));
+        let reference = RcReference::new(rc.clone(), |string| string);
+
+        let reference = reference.try_unwrap_box().unwrap_err();
+        assert_eq!(*reference, "Foo");
+
+        drop(rc);
+
+        assert_eq!(*reference.try_unwrap_box().unwrap(), "Foo");
+    }
+
+    #[test]
+    fn reproject_in_place() {
+        let arc = Arc::new(String::from("Hello World!"));
+
+        let mut window = ArcReference::new(arc.clone(), |string| &string[0..5]);
+        assert_eq!(&*window, "Hello");
+        assert_eq!(Arc::strong_count(&arc), 2);
+
+        window.reproject_in_place(|string| &string[6..11]);
+        assert_eq!(&*window, "World");
+        assert_eq!(Arc::strong_count(&arc), 2);
+    }
+
+    #[test]
+    fn try_leak_unique() {
+        let world = ArcReference::new(Arc::new(String::from("Hello World!")), |string| {
+            &string[6..11]
+        });
+
+        let world: &'static str = world.try_leak().unwrap();
+        assert_eq!(world, "World");
+    }
+
+    #[test]
+    fn try_leak_shared() {
+        let rc = Rc::new(String::from("Hello World!"));
+        let world = RcReference::new(rc.clone(), |string| &string[6..11]);
+
+        let world = world.try_leak().unwrap_err();
+        assert_eq!(&*world, "World");
+        assert_eq!(Rc::strong_count(&rc), 2);
+    }
+
+    #[test]
+    fn projector() {
+        struct Owner {
+            values: Option<Vec<Result<String, u32>>>,
+        }
+
+        let arc = Arc::new(Owner {
+            values: Some(vec![Err(404), Ok(String::from("Foo"))]),
+        });
+
+        let root = ArcReference::new(arc.clone(), |owner| owner);
+
+        let foo = root
+            .project()
+            .field(|owner| &owner.values)
+            .some()
+            .index(1)
+            .ok()
+            .field(String::as_str)
+            .build();
+
+        assert_eq!(Arc::strong_count(&arc), 3);
+        assert_eq!(foo.as_deref(), Some("Foo"));
+
+        let error = root
+            .project()
+            .field(|owner| &owner.values)
+            .some()
+            .index(0)
+            .ok()
+            .build();
+        assert!(error.is_none());
+
+        let out_of_bounds = root
+            .project()
+            .field(|owner| &owner.values)
+            .some()
+            .index(2)
+            .build();
+        assert!(out_of_bounds.is_none());
+    }
+
+    #[test]
+    fn swap() {
+        let arc = Arc::new(String::from("Hello World!"));
+        let other = Arc::new(String::from("Foo"));
+
+        let mut hello = ArcReference::new(arc.clone(), |string| &string[0..5]);
+        let mut world = ArcReference::new(arc.clone(), |string| &string[6..11]);
+        let mut foo = ArcReference::new(other.clone(), |string| &string[..]);
+
+        drop(arc);
+        drop(other);
+
+        std::mem::swap(&mut hello, &mut world);
+        assert_eq!(format!("{hello} {world}"), "World Hello");
+
+        std::mem::swap(&mut hello, &mut foo);
+        assert_eq!(format!("{hello} {world} {foo}"), "Foo Hello World");
+        assert_eq!(hello.source().as_str(), "Foo");
+        assert_eq!(foo.source().as_str(), "Hello World!");
+    }
+
+    #[test]
+    fn field() {
+        struct Foo {
+            a: u8,
+            c: String,
+        }
+
+        let arc = Arc::new(Foo {
+            a: 42,
+            c: String::from("Foo"),
+        });
+        let rc = Rc::new(Foo {
+            a: 42,
+            c: String::from("Foo"),
+        });
+
+        let arc_a = arc_field(&arc, |foo| &foo.a);
+        let arc_c = arc_field(&arc, |foo| foo.c.as_str());
+        let rc_c = rc_field(&rc, |foo| &foo.c);
+
+        drop(arc);
+        drop(rc);
+
+        assert_eq!(*arc_a, 42);
+        assert_eq!(&*arc_c, "Foo");
+        assert_eq!(*rc_c, "Foo");
+        assert_eq!(Arc::strong_count(arc_a.source()), 2);
+    }
+
+    #[test]
+    fn pool() {
+        let arc = Arc::new(String::from("Hello World!"));
+
+        let pool = ArcReferencePool::new(arc.clone(), 16);
+
+        let references: Vec<_> = (0..20)
+            .map(|i| pool.acquire(|string| &string[i % 12..]))
+            .collect();
+
+        assert_eq!(&*references[6], "World!");
+        assert_eq!(Arc::strong_count(&arc), 2 + 32);
+
+        drop(pool);
+        assert_eq!(Arc::strong_count(&arc), 1 + 20);
+
+        let world = references[6].clone();
+        drop(references);
+        assert_eq!(Arc::strong_count(&arc), 2);
+
+        drop(arc);
+        assert_eq!(&*world, "World!");
+        assert_eq!(Arc::strong_count(world.source()), 1);
+    }
+
+    #[test]
+    fn ordered_by_owner() {
+        use std::collections::BTreeMap;
+
+        let a = Arc::new(String::from("Hello World!"));
+        let b = Arc::new(String::from("Hello World!"));
+
+        let hello = OrderedByOwner(ArcReference::new(a.clone(), |string| &string[0..5]));
+        let whole = OrderedByOwner(ArcReference::new(a.clone(), |string| &string[..]));
+        let world = OrderedByOwner(ArcReference::new(a.clone(), |string| &string[6..11]));
+        let other = OrderedByOwner(ArcReference::new(b.clone(), |string| &string[0..5]));
+
+        assert_ne!(hello, whole);
+        assert_ne!(hello, other);
+        assert_eq!(hello, hello.clone());
+        assert!(hello < world);
+        assert_eq!(hello < other, Arc::as_ptr(&a) < Arc::as_ptr(&b));
+
+        let mut map = BTreeMap::new();
+        for (i, reference) in [&hello, &whole, &world, &other, &hello]
+            .into_iter()
+            .enumerate()
+        {
+            map.insert(reference.clone(), i);
+        }
+
+        assert_eq!(map.len(), 4);
+        assert_eq!(map[&hello], 4);
+
+        let order: Vec<_> = map.keys().cloned().collect();
+        let mut sorted = order.clone();
+        sorted.sort();
+        assert_eq!(order, sorted);
+    }
+
+    #[test]
+    fn read_only_slice() {
+        fn parse_key_value(input: &[u8]) -> Option<(&[u8], &[u8])> {
+            let split = input.iter().position(|&byte| byte == b'=')?;
+            Some((&input[..split], &input[split + 1..]))
+        }
+
+        let arc = Arc::new(b"answer=42".to_vec());
+        let buffer = ArcReference::new(arc.clone(), |vec| &vec[..]);
+
+        drop(arc);
+
+        let (key, value) = parse_key_value(buffer.as_read_only_slice()).unwrap();
+        assert_eq!(key, b"answer");
+        assert_eq!(value, b"42");
+
+        let (buffer, slice) = buffer.into_owned_and_slice();
+        assert_eq!(unsafe { &*slice }, buffer.as_read_only_slice());
+    }
+
+    #[test]
+    fn weak_reference() {
+        let arc = Arc::new(String::from("Hello World!"));
+
+        let world = ArcReference::new(arc.clone(), |string| &string[6..11]).downgrade();
+        assert_eq!(&*world.upgrade().unwrap(), "World");
+
+        drop(arc);
+        assert!(world.upgrade().is_none());
+        assert_eq!(world.strong_count(), 0);
+    }
+
+    #[test]
+    fn value_comparisons() {
+        use std::collections::HashSet;
+
+        let hello = ArcReference::new(Arc::new(String::from("Hello")), |string| string.as_str());
+        let world = ArcReference::new(Arc::new(*b"World"), |bytes| {
+            std::str::from_utf8(bytes).unwrap()
+        });
+        let other = ArcReference::new(Arc::new(String::from("Hello World!")), |string| {
+            &string[0..5]
+        });
+
+        assert_eq!(hello, hello.clone());
+        assert_ne!(hello, world);
+        assert!(hello < world);
+        assert_eq!(hello.cmp(&other), Ordering::Equal);
+
+        let set: HashSet<_> = [hello.clone(), other].into_iter().collect();
+        assert_eq!(set.len(), 1);
+    }
+
+    #[test]
+    fn weak_set() {
+        let set = Arc::new(WeakArcReferenceSet::<String, str>::new());
+        let created = Arc::new(AtomicUsize::new(0));
+
+        let intern = {
+            let created = created.clone();
+            move |set: &WeakArcReferenceSet<String, str>, value: &str| {
+                set.get_or_insert_with(value, || {
+                    created.fetch_add(1, atomic::Ordering::SeqCst);
+                    ArcReference::new(Arc::new(String::from(value)), |string| string.as_str())
+                })
+            }
+        };
+
+        let threads: Vec<_> = (0..8)
+            .map(|_| {
+                let set = set.clone();
+                let intern = intern.clone();
+                std::thread::spawn(move || intern(&set, "Foo"))
+            })
+            .collect();
+
+        let references: Vec<_> = threads
+            .into_iter()
+            .map(|thread| thread.join().unwrap())
+            .collect();
+
+        assert_eq!(created.load(atomic::Ordering::SeqCst), 1);
+        assert!(references
+            .iter()
+            .all(|reference| Arc::ptr_eq(reference.source(), references[0].source())));
+        assert_eq!(&*set.get("Foo").unwrap(), "Foo");
+
+        drop(references);
+        assert!(set.get("Foo").is_none());
+
+        let foo = intern(&set, "Foo");
+        assert_eq!(created.load(atomic::Ordering::SeqCst), 2);
+        assert_eq!(&*foo, "Foo");
+    }
+
+    #[test]
+    fn weak_set_panicking_initializer() {
+        let set = WeakArcReferenceSet::<String, str>::new();
+        let a = set.get_or_insert_with("a", || {
+            ArcReference::new(Arc::new(String::from("a")), |string| string.as_str())
+        });
+
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            set.get_or_insert_with("b", || panic!("initializer failed"))
+        }));
+        assert!(result.is_err());
+
+        assert!(set.get("b").is_none());
+        assert_eq!(set.get("a"), Some(a));
+
+        let b = set.get_or_insert_with("b", || {
+            ArcReference::new(Arc::new(String::from("b")), |string| string.as_str())
+        });
+        assert_eq!(set.get("b"), Some(b));
+    }
+
+    #[test]
+    fn weak_set_sweep() {
+        let set = WeakArcReferenceSet::<String, str>::new();
+
+        for i in 0..1000 {
+            let value = i.to_string();
+            set.get_or_insert_with(&value, || {
+                ArcReference::new(Arc::new(value.clone()), |string| string.as_str())
+            });
+        }
+
+        assert!(set.state.lock().unwrap().len < WeakSetState::<String, str>::MIN_SWEEP);
+    }
+
+    #[cfg(feature = "validate")]
+    #[derive(Debug)]
+    #[repr(align(8))]
+    struct AlignedBytes([u8; 16]);
+
+    #[cfg(feature = "validate")]
+    #[test]
+    fn try_new_aligned() {
+        let bytes = Arc::new(AlignedBytes(*b"\x01\x00\x00\x00\x02\x00\x00\x00abcdefgh"));
+
+        let second = unsafe {
+            ArcReference::try_new_aligned(bytes.clone(), |bytes| {
+                bytes.0[4..].as_ptr() as *const u32
+            })
+        }
+        .unwrap();
+
+        assert_eq!(*second, u32::from_ne_bytes([2, 0, 0, 0]));
+    }
+
+    #[cfg(feature = "validate")]
+    #[test]
+    fn try_new_misaligned() {
+        let bytes = Rc::new(AlignedBytes([0; 16]));
+
+        let owner = unsafe {
+            RcReference::try_new_aligned(bytes.clone(), |bytes| bytes.0[1..].as_ptr() as *const u32)
+        }
+        .unwrap_err();
+
+        assert!(Rc::ptr_eq(&owner, &bytes));
+    }
+
+    #[test]
+    fn graph() {
+        struct Node {
+            name: &'static str,
+            edges: Vec<usize>,
+        }
+
+        impl GraphNode for Node {
+            fn edges(&self) -> &[usize] {
+                &self.edges
+            }
+        }
+
+        let arc = Arc::new(vec![
+            Node {
+                name: "a",
+                edges: vec![1, 2],
+            },
+            Node {
+                name: "b",
+                edges: vec![2],
+            },
+            Node {
+                name: "c",
+                edges: vec![0, 3],
+            },
+        ]);
+
+        let graph = ArcReference::new(arc.clone(), |nodes| &nodes[..]);
+        drop(arc);
+
+        let root = graph.element(0).unwrap();
+        let b = graph.neighbor(&root, 0).unwrap();
+        drop(root);
+
+        let c = graph.neighbor(&b, 0).unwrap();
+        assert_eq!(c.name, "c");
+        assert!(graph.neighbor(&c, 1).is_none());
+        assert!(graph.neighbor(&c, 2).is_none());
+
+        drop(graph);
+
+        assert_eq!(b.name, "b");
+        assert!(Arc::ptr_eq(b.source(), c.source()));
+        assert_eq!(Arc::strong_count(c.source()), 2);
+    }
+
+    #[test]
+    fn reference_group() {
+        struct Owner {
+            values: Vec<u32>,
+            drops: Arc<AtomicUsize>,
+        }
+
+        impl Drop for Owner {
+            fn drop(&mut self) {
+                self.drops.fetch_add(1, atomic::Ordering::SeqCst);
+            }
+        }
+
+        let drops = Arc::new(AtomicUsize::new(0));
+        let group = ReferenceGroup::new(Arc::new(Owner {
+            values: (0..1000).collect(),
+            drops: drops.clone(),
+        }));
+
+        let references: Vec<_> = (0..1000)
+            .map(|i| group.get(|owner| &owner.values[i]))
+            .collect();
+
+        assert_eq!(Arc::strong_count(group.source()), 1);
+        assert!(references
+            .iter()
+            .enumerate()
+            .all(|(i, value)| **value == i as u32));
+
+        let owned = references[42].to_reference();
+        drop(references);
+        drop(group);
+
+        assert_eq!(drops.load(atomic::Ordering::SeqCst), 0);
+        assert_eq!(*owned, 42);
+
+        drop(owned);
+        assert_eq!(drops.load(atomic::Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn hex_dump() {
+        let buffer = ArcReference::new(
+            Arc::new(b"Hello World!\n\x00\x01\x02\xff".to_vec()),
+            |vec| &vec[..],
+        );
+
+        assert_eq!(
+            format!("{:?}", buffer.hex_dump()),
+            "00000000  48 65 6c 6c 6f 20 57 6f  72 6c 64 21 0a 00 01 02  |Hello World!....|\n\
+             00000010  ff                                                |.|"
+        );
+        assert_eq!(format!("{buffer:?}"), format!("{:?}", &buffer[..]));
+    }
+
+    #[test]
+    fn project_through() {
+        struct Server {
+            host: String,
+            ports: (u16, u16),
+        }
+
+        struct Config {
+            servers: Vec<Server>,
+        }
+
+        struct App {
+            config: Box<Config>,
+        }
+
+        let arc = Arc::new(Box::new(App {
+            config: Box::new(Config {
+                servers: vec![
+                    Server {
+                        host: String::from("localhost"),
+                        ports: (80, 443),
+                    },
+                    Server {
+                        host: String::from("example.com"),
+                        ports: (8080, 8443),
+                    },
+                ],
+            }),
+        }));
+
+        let host = project_through!(arc.clone() => .**.config.*.servers[1].host);
+        let port = project_through!(arc.clone() => .config.servers[0].ports.1);
+        let config = project_through!(arc.clone() => .config.*);
+
+        drop(arc);
+
+        assert_eq!(*host, "example.com");
+        assert_eq!(*port, 443);
+        assert_eq!(config.servers.len(), 2);
+
+        let rc = Rc::new(vec![(1, String::from("Foo"))]);
+        let foo = project_through!(RcReference, rc => [0].1);
+        assert_eq!(*foo, "Foo");
+    }
+
+    #[test]
+    fn merge_adjacent() {
+        let arc: Arc<[u8]> = Arc::from(&b"abcdefghij"[..]);
+        let whole = ArcReference::new(arc.clone(), |slice| slice);
+
+        let (prefix, rest) = whole.clone().take_prefix(4);
+        let merged = ArcReference::merge_adjacent(prefix, rest, |slice| slice).unwrap();
+        assert!(std::ptr::eq(&*merged, &*whole));
+
+        let (prefix, rest) = whole.clone().take_prefix(4);
+        assert!(ArcReference::merge_adjacent(rest, prefix, |slice| slice).is_none());
+
+        let (prefix, rest) = whole.clone().take_prefix(4);
+        let (_, tail) = rest.take_prefix(2);
+        assert!(ArcReference::merge_adjacent(prefix, tail, |slice| slice).is_none());
+
+        let other: Arc<[u8]> = Arc::from(&b"abcdefghij"[..]);
+        let other = ArcReference::new(other, |slice| slice);
+        let (prefix, _) = whole.take_prefix(4);
+        let (_, rest) = other.take_prefix(4);
+        assert!(ArcReference::merge_adjacent(prefix, rest, |slice| slice).is_none());
+
+        assert_eq!(&*merged, b"abcdefghij");
+        assert_eq!(Arc::strong_count(&arc), 2);
+    }
+
+    #[test]
+    fn merge_adjacent_vec() {
+        let vec = Arc::new(b"abcdefghij".to_vec());
+        let whole = ArcReference::new(vec.clone(), |vec| &vec[..]);
+
+        let (prefix, rest) = whole.clone().take_prefix(2);
+        let (middle, _) = rest.take_prefix(4);
+        let merged = ArcReference::merge_adjacent(prefix, middle, |vec| &vec[..]).unwrap();
+        assert_eq!(&*merged, b"abcdef");
+        assert!(std::ptr::eq(merged.as_ptr(), whole.as_ptr()));
+
+        let (prefix, rest) = whole.take_prefix(4);
+        assert!(ArcReference::merge_adjacent(prefix, rest, |vec| &vec[..4]).is_none());
+    }
+
+    #[test]
+    fn merge_adjacent_outside_whole() {
+        #[repr(C)]
+        struct Halves([u8; 4], [u8; 4]);
+
+        static HALVES: Halves = Halves(*b"abcd", *b"efgh");
+
+        let owner = Arc::new(());
+        let a = ArcReference::new(owner.clone(), |_| &HALVES.0[..]);
+        let b = ArcReference::new(owner.clone(), |_| &HALVES.1[..]);
+        assert_eq!(a.as_ptr_range().end, b.as_ptr());
+        assert!(ArcReference::merge_adjacent(a, b, |_| &HALVES.0[..]).is_none());
+    }
+
+    #[test]
+    fn take_prefix() {
+        let arc = Arc::new(b"abcdefghij".to_vec());
+        let mut rest = ArcReference::new(arc.clone(), |vec| &vec[..]);
+
+        drop(arc);
+
+        let mut records = Vec::new();
+        while rest.len() >= 4 {
+            let (record, remainder) = rest.take_prefix(4);
+            records.push(record);
+            rest = remainder;
+        }
+
+        assert_eq!(records.len(), 2);
+        assert_eq!(&*records[0], b"abcd");
+        assert_eq!(&*records[1], b"efgh");
+        assert_eq!(&*rest, b"ij");
+        assert_eq!(Arc::strong_count(rest.source()), 3);
+    }
+
+    #[test]
+    fn take_prefix_str() {
+        let line = ArcReference::new(Arc::new(String::from("GET /index.html")), |string| {
+            string.as_str()
+        });
+
+        let (method, rest) = line.take_prefix_str(3);
+        let (_, path) = rest.take_prefix_str(1);
+
+        assert_eq!(&*method, "GET");
+        assert_eq!(&*path, "/index.html");
+    }
+
+    #[test]
+    fn atomic_option_get_or_init() {
+        let slot = Arc::new(AtomicOptionArcReference::<String, str>::default());
+        let barrier = Arc::new(Barrier::new(8));
+        let installed = Arc::new(AtomicUsize::new(0));
+
+        assert!(slot.get().is_none());
+
+        let threads: Vec<_> = (0..8)
+            .map(|i| {
+                let slot = slot.clone();
+                let barrier = barrier.clone();
+                let installed = installed.clone();
+                std::thread::spawn(move || {
+                    barrier.wait();
+
+                    let value = slot.get_or_init(|| {
+                        ArcReference::new(Arc::new(format!("thread {i}")), |string| string.as_str())
+                    });
+
+                    if *value == *format!("thread {i}") {
+                        installed.fetch_add(1, atomic::Ordering::SeqCst);
+                    }
+
+                    value
+                })
+            })
+            .collect();
+
+        let values: Vec<_> = threads
+            .into_iter()
+            .map(|thread| thread.join().unwrap())
+            .collect();
+
+        assert_eq!(installed.load(atomic::Ordering::SeqCst), 1);
+        assert!(values
+            .iter()
+            .all(|value| Arc::ptr_eq(value.source(), values[0].source())));
+
+        let mut slot = Arc::try_unwrap(slot).ok().unwrap();
+        assert_eq!(slot.get().unwrap(), values[0]);
+        assert_eq!(Arc::strong_count(values[0].source()), 9);
+
+        assert_eq!(slot.take().unwrap(), values[0]);
+        assert!(slot.get().is_none());
+        assert_eq!(Arc::strong_count(values[0].source()), 8);
+    }
+
+    #[test]
+    fn target_offset() {
+        #[derive(Debug, Clone, PartialEq)]
+        struct Owner {
+            id: u64,
+            name: String,
+            score: u32,
+        }
+
+        fn serialize(owner: &Owner) -> String {
+            format!("{}:{}:{}", owner.id, owner.name, owner.score)
+        }
+
+        fn deserialize(string: &str) -> Owner {
+            let mut parts = string.split(':');
+            Owner {
+                id: parts.next().unwrap().parse().unwrap(),
+                name: parts.next().unwrap().to_owned(),
+                score: parts.next().unwrap().parse().unwrap(),
+            }
+        }
+
+        let owner = Arc::new(Owner {
+            id: 1,
+            name: String::from("Foo"),
+            score: 42,
+        });
+
+        let score = ArcReference::new(owner.clone(), |owner| &owner.score);
+        let offset = score.target_offset().unwrap();
+
+        let name = ArcReference::new(owner.clone(), |owner| owner.name.as_str());
+        assert_eq!(name.target_offset(), None);
+
+        let restored = Arc::new(deserialize(&serialize(&owner)));
+        assert_eq!(*restored, *owner);
+
+        let score = unsafe { ArcReference::<_, u32>::from_owner_offset(restored, offset) };
+        assert_eq!(*score, 42);
+    }
+
+    #[test]
+    fn fork() {
+        let arc = Arc::new(String::from("Hello World!"));
+        let world = ArcReference::new(arc.clone(), |string| &string[6..11]);
+
+        let (a, b) = world.fork();
+        assert_eq!(format!("{a} {b}"), "World World");
+        assert_eq!(Arc::strong_count(&arc), 4);
+        drop((a, b));
+
+        let forks = world.fork_n(8);
+        assert_eq!(Arc::strong_count(&arc), 10);
+
+        let threads: Vec<_> = forks
+            .into_iter()
+            .map(|world| std::thread::spawn(move || assert_eq!(&*world, "World")))
+            .collect();
+
+        for thread in threads {
+            thread.join().unwrap();
+        }
+
+        assert_eq!(Arc::strong_count(&arc), 2);
+    }
+
+    #[cfg(feature = "track-drops")]
+    #[test]
+    fn acyclic() {
+        struct Node {
+            name: String,
+            parent: std::sync::OnceLock<ArcReference<Node, str>>,
+        }
+
+        let root = Arc::new(Node {
+            name: String::from("root"),
+            parent: std::sync::OnceLock::new(),
+        });
+        let child = Arc::new(Node {
+            name: String::from("child"),
+            parent: std::sync::OnceLock::new(),
+        });
+
+        child
+            .parent
+            .set(ArcReference::new(root.clone(), |node| node.name.as_str()))
+            .unwrap();
+
+        let parent = ArcReference::new(child.clone(), |node| node.parent.get().unwrap());
+        parent.assert_acyclic();
+        assert_eq!(&**parent, "root");
+    }
+
+    #[cfg(feature = "track-drops")]
+    #[test]
+    #[should_panic(expected = "forms a cycle")]
+    fn cyclic() {
+        struct Node {
+            name: String,
+            this: std::sync::OnceLock<ArcReference<Node, str>>,
+        }
+
+        let node = Arc::new(Node {
+            name: String::from("node"),
+            this: std::sync::OnceLock::new(),
+        });
+
+        node.this
+            .set(ArcReference::new(node.clone(), |node| node.name.as_str()))
+            .unwrap();
+
+        ArcReference::new(node.clone(), |node| node.this.get().unwrap()).assert_acyclic();
+    }
+
+    #[cfg(feature = "track-drops")]
+    #[test]
+    #[should_panic(expected = "forms a cycle")]
+    fn cyclic_through_heap() {
+        struct Node {
+            name: String,
+            children: std::sync::OnceLock<Vec<ArcReference<Node, str>>>,
+        }
+
+        let node = Arc::new(Node {
+            name: String::from("node"),
+            children: std::sync::OnceLock::new(),
+        });
+
+        node.children
+            .set(vec![ArcReference::new(node.clone(), |node| {
+                node.name.as_str()
+            })])
+            .ok()
+            .unwrap();
+
+        let child = ArcReference::new(node.clone(), |node| &node.children.get().unwrap()[0]);
+        child.assert_acyclic();
+    }
+
+    #[test]
+    fn downcast_target() {
+        struct Foo {
+            value: u32,
+            name: String,
+        }
+
+        let arc = Arc::new(Foo {
+            value: 42,
+            name: String::from("Foo"),
+        });
+
+        let value = ArcReference::new(arc.clone(), |foo| &foo.value as &(dyn Any + Send + Sync));
+        let name = RcReference::new(Rc::new(String::from("Foo")), |name| name as &dyn Any);
+
+        let value = value.downcast_target::<String>().unwrap_err();
+        let value = value.downcast_target::<u32>().unwrap();
+        assert_eq!(*value, 42);
+
+        let name = name.downcast_target::<String>().unwrap();
+        assert_eq!(*name, "Foo");
+        assert_eq!(arc.name, "Foo");
+    }
+
+    #[test]
+    fn any_reference() {
+        use std::collections::BTreeSet;
+
+        let rc = Rc::new(String::from("banana cherry"));
+        let arc = Arc::new(vec![String::from("apple"), String::from("date")]);
+
+        let mut set: BTreeSet<Box<dyn AnyReference<str>>> = BTreeSet::new();
+        set.insert(Box::new(RcReference::new(rc.clone(), |string| {
+            &string[7..]
+        })));
+        set.insert(Box::new(ArcReference::new(arc.clone(), |vec| {
+            vec[1].as_str()
+        })));
+        set.insert(Box::new(RcReference::new(rc.clone(), |string| {
+            &string[..6]
+        })));
+        set.insert(Box::new(ArcReference::new(arc.clone(), |vec| {
+            vec[0].as_str()
+        })));
+        set.insert(Box::new(ArcReference::new(Arc::new("apple"), |string| {
+            *string
+        })));
+
+        let values: Vec<_> = set.iter().map(|reference| reference.target()).collect();
+        assert_eq!(values, ["apple", "banana", "cherry", "date"]);
+    }
+
+    #[test]
+    fn as_str_checked() {
+        let arc = Arc::new(b"Hello \xffWorld".to_vec());
+
+        let hello = ArcReference::new(arc.clone(), |bytes| &bytes[..5]);
+        let invalid = ArcReference::new(arc.clone(), |bytes| &bytes[..]);
 
         drop(arc);
 
-        assert_eq!(format!("{hello} {world}"), "Hello World");
+        assert_eq!(hello.as_str_checked(), Ok("Hello"));
+        assert_eq!(invalid.as_str_checked().unwrap_err().valid_up_to(), 6);
     }
 
     #[test]
-    fn access_source() {
-        let rc = Rc::new(String::from("Hello World!"));
+    fn new_from_weak() {
+        struct Parent {
+            name: String,
+        }
 
-        let hello = RcReference::new(rc.clone(), |string| &string[0..5]);
+        let parent = Arc::new(Parent {
+            name: String::from("root"),
+        });
+        let weak = Arc::downgrade(&parent);
 
-        drop(rc);
+        let name = ArcReference::new_from_weak(&weak, |parent| parent.name.as_str()).unwrap();
+        assert_eq!(&*name, "root");
 
-        let world = RcReference::new(hello.source().clone(), |string| &string[6..11]);
+        drop(parent);
+        drop(name);
 
-        assert_eq!(format!("{hello} {world}"), "Hello World");
+        assert!(ArcReference::new_from_weak(&weak, |parent| parent.name.as_str()).is_none());
     }
 
     #[test]
-    fn threaded() {
-        macro_rules! with_cloned {
-            ([$($variables: ident),* $(,)?], $body: stmt) => {
-                {
-                    $(let $variables = $variables.clone();)*
+    fn map_via() {
+        struct Address {
+            host: String,
+            port: u16,
+        }
 
-                    $body
+        let arc = Arc::new(String::from("server = example.com:8080"));
+        let address = ArcReference::new(arc.clone(), |string| &string[9..]);
+
+        let host = address.map_via(
+            |address| {
+                let (host, port) = address.split_once(':').unwrap();
+                Address {
+                    host: host.to_owned(),
+                    port: port.parse().unwrap(),
                 }
-            };
+            },
+            |address| address.host.as_str(),
+        );
+
+        assert_eq!(Arc::strong_count(&arc), 1);
+        assert_eq!(&*host, "example.com");
+        assert_eq!(host.source().port, 8080);
+    }
+
+    #[test]
+    fn cell() {
+        struct Counter {
+            name: &'static str,
+            count: Cell<i32>,
         }
 
-        let arc = Arc::new(String::from("Hello World!"));
+        let rc = Rc::new(Counter {
+            name: "counter",
+            count: Cell::new(0),
+        });
 
-        let hello = ArcReference::new(arc.clone(), |string| &string[0..5]);
+        let count = RcReference::new(rc.clone(), |counter| &counter.count);
+        let other = count.clone();
+
+        count.set(5);
+        assert_eq!(other.replace(7), 5);
+        assert_eq!(count.get(), 7);
+        assert_eq!(rc.count.get(), 7);
+        assert_eq!(rc.name, "counter");
+    }
+
+    #[test]
+    fn shared_reference_arena() {
+        let arc = Arc::new((0..1000u32).collect::<Vec<_>>());
+
+        let references: Vec<_> = (0..1000)
+            .map(|i| ArcReference::new(arc.clone(), |vec| &vec[i]))
+            .collect();
+        assert_eq!(Arc::strong_count(&arc), 1001);
+
+        let mut arena = SharedReferenceArena::coalesce(references).ok().unwrap();
+        assert_eq!(Arc::strong_count(&arc), 2);
+        assert_eq!(arena.len(), 1000);
+        assert!(arena.iter().copied().eq(0..1000));
+
+        let index = arena.push(|vec| &vec[500]);
+        assert_eq!(arena.get(index), Some(&500));
+
+        let owned = arena.reference(index).unwrap();
+        drop(arena);
+        drop(arc);
+        assert_eq!(*owned, 500);
+        assert_eq!(Arc::strong_count(owned.source()), 1);
+
+        let mixed = vec![
+            owned.clone(),
+            ArcReference::new(Arc::new(vec![1]), |vec| &vec[0]),
+        ];
+        assert_eq!(
+            SharedReferenceArena::coalesce(mixed).err().unwrap().len(),
+            2
+        );
+    }
+
+    #[test]
+    fn references_for_each() {
+        let vec = Arc::new(vec![String::from("a"), String::from("b")]);
+        let slice: Arc<[u32]> = Arc::from([1, 2, 3]);
+
+        let strings = super::references_for_each(&vec);
+        let numbers = super::references_for_each(&slice);
+
+        assert_eq!(Arc::strong_count(&vec), 3);
+
+        drop(vec);
+        drop(slice);
+
+        assert_eq!(strings[0].as_str(), "a");
+        assert_eq!(strings[1].as_str(), "b");
+        assert_eq!(numbers.iter().map(|number| **number).sum::<u32>(), 6);
+    }
+
+    #[test]
+    fn weak_debug() {
+        let arc = Arc::new(vec![1, 2, 3]);
+        let weak = ArcReference::new(arc.clone(), |vec| &vec[1..]).downgrade();
+
+        assert_eq!(format!("{weak:?}"), "[2, 3]");
+        assert_eq!(Arc::strong_count(&arc), 1);
+
+        drop(arc);
+        assert_eq!(format!("{weak:?}"), "ArcWeakReference(<dead>)");
+
+        let rc = Rc::new(String::from("Foo"));
+        let weak = RcReference::new(rc.clone(), |string| string.as_str()).downgrade();
+        assert_eq!(format!("{weak:?}"), "\"Foo\"");
+
+        drop(rc);
+        assert_eq!(format!("{weak:?}"), "RcWeakReference(<dead>)");
+    }
+
+    #[test]
+    fn into_opaque() {
+        fn describe(reference: &dyn Deref<Target = str>) -> String {
+            format!("<{}>", &**reference)
+        }
+
+        let arc = Arc::new(String::from("Hello World!"));
         let world = ArcReference::new(arc.clone(), |string| &string[6..11]);
+        let opaque = world.clone().into_opaque();
 
-        let barrier = Arc::new(Barrier::new(3));
+        drop(world);
+        drop(arc);
 
-        let a = with_cloned!(
-            [barrier, hello, world],
-            std::thread::spawn(move || {
-                barrier.wait();
-                assert_eq!(format!("{hello} {world}"), "Hello World");
+        let opaque = std::thread::spawn(move || {
+            assert_eq!(&**opaque, "World");
+            opaque
+        })
+        .join()
+        .unwrap();
+
+        assert_eq!(describe(&*opaque), "<World>");
+    }
+
+    #[test]
+    fn arc_slab() {
+        #[derive(Debug, Clone, PartialEq)]
+        struct Position {
+            x: i32,
+            y: i32,
+        }
+
+        let mut slab = ArcSlab::new();
+        let a = slab.insert(Position { x: 1, y: 2 });
+        let b = slab.insert(Position { x: 3, y: 4 });
+
+        let first = slab.get(a).unwrap();
+        assert!(slab.get(2).is_none());
+
+        let c = slab.insert(Position { x: 5, y: 6 });
+        assert_eq!(slab.len(), 3);
+
+        let second = slab.get(b).unwrap();
+        let third = slab.get(c).unwrap();
+
+        drop(slab);
+
+        assert_eq!(*first, Position { x: 1, y: 2 });
+        assert_eq!(second.y, 4);
+        assert_eq!(third.x, 5);
+        assert_eq!(first.source().len(), 2);
+        assert!(Arc::ptr_eq(second.source(), third.source()));
+    }
+
+    #[test]
+    fn atomic_arc_reference_update() {
+        struct Config {
+            version: u32,
+            settings: (u32, u32),
+        }
+
+        let config = Arc::new(AtomicArcReference::new(ArcReference::new(
+            Arc::new(Config {
+                version: 0,
+                settings: (0, 0),
+            }),
+            |config| &config.settings,
+        )));
+
+        let done = Arc::new(std::sync::atomic::AtomicBool::new(false));
+
+        let readers: Vec<_> = (0..4)
+            .map(|_| {
+                let config = config.clone();
+                let done = done.clone();
+                std::thread::spawn(move || {
+                    let mut last = 0;
+                    while !done.load(atomic::Ordering::SeqCst) {
+                        let settings = config.load();
+                        assert_eq!(settings.0, settings.1);
+                        assert!(settings.source().version >= last);
+                        last = settings.source().version;
+                    }
+                })
             })
-        );
+            .collect();
 
-        let b = with_cloned!(
-            [barrier, hello, world],
-            std::thread::spawn(move || {
-                barrier.wait();
-                assert_eq!(format!("{world} {hello}"), "World Hello");
+        let writers: Vec<_> = (0..4)
+            .map(|_| {
+                let config = config.clone();
+                std::thread::spawn(move || {
+                    for _ in 0..100 {
+                        config.update(
+                            |settings| Config {
+                                version: settings.0 + 1,
+                                settings: (settings.0 + 1, settings.1 + 1),
+                            },
+                            |config| &config.settings,
+                        );
+                    }
+                })
             })
+            .collect();
+
+        for writer in writers {
+            writer.join().unwrap();
+        }
+        done.store(true, atomic::Ordering::SeqCst);
+        for reader in readers {
+            reader.join().unwrap();
+        }
+
+        let settings = Arc::try_unwrap(config).ok().unwrap().into_inner();
+        assert_eq!(*settings, (400, 400));
+        assert_eq!(settings.source().version, 400);
+    }
+
+    #[test]
+    fn into_cow() {
+        struct Counted(String, Arc<AtomicUsize>);
+
+        impl Clone for Counted {
+            fn clone(&self) -> Self {
+                self.1.fetch_add(1, atomic::Ordering::SeqCst);
+                Self(self.0.clone(), self.1.clone())
+            }
+        }
+
+        let clones = Arc::new(AtomicUsize::new(0));
+        let unique = ArcReference::new(
+            Arc::new(Counted(String::from("Foo"), clones.clone())),
+            |counted| counted,
         );
 
-        let c = std::thread::spawn(move || {
-            drop(arc);
-            barrier.wait();
-        });
+        let owned = unique.into_cow_unwrapped();
+        assert_eq!(owned.0, "Foo");
+        assert!(matches!(owned, Cow::Owned(_)));
+        assert_eq!(clones.load(atomic::Ordering::SeqCst), 0);
 
-        a.join().unwrap();
-        b.join().unwrap();
-        c.join().unwrap();
+        let arc = Arc::new(Counted(String::from("Bar"), clones.clone()));
+        let shared = ArcReference::new(arc.clone(), |counted| counted);
+
+        let owned = shared.into_cow_unwrapped();
+        assert_eq!(owned.0, "Bar");
+        assert_eq!(clones.load(atomic::Ordering::SeqCst), 1);
+        assert_eq!(Arc::strong_count(&arc), 1);
+
+        let string = Arc::new(String::from("Hello World!"));
+        let world = ArcReference::new(string.clone(), |string| &string[6..11]);
+        let world: Cow<'static, str> = world.into_cow_owned();
+        drop(string);
+        assert_eq!(world, "World");
+
+        let numbers = RcReference::new(Rc::new(vec![1, 2, 3]), |vec| &vec[1..]);
+        let numbers: Cow<'static, [i32]> = numbers.into_cow_owned();
+        assert_eq!(&*numbers, [2, 3]);
     }
 
     #[test]
-    fn test_multiple_arc() {
-        struct Foo {
-            a: u8,
-            b: u32,
-            c: String,
+    fn call() {
+        type Callback = dyn Fn(i32) -> i32 + Send + Sync;
+
+        struct Registry {
+            double: Box<Callback>,
+            add: Box<dyn Fn(i32, i32) -> i32 + Send + Sync>,
         }
 
-        let foo = Arc::new(Foo {
-            a: 42,
-            b: 1024,
-            c: String::from("Foo"),
+        let registry = Arc::new(Registry {
+            double: Box::new(|x| x * 2),
+            add: Box::new(|a, b| a + b),
         });
 
-        let (a, b, c) = arc_multiple(&foo, |ctx, value| {
-            (
-                ctx.new_reference(&value.a),
-                ctx.new_reference(&value.b),
-                ctx.new_reference(&value.c),
-            )
-        });
+        let double = ArcReference::new(registry.clone(), |registry| &*registry.double);
+        drop(registry);
 
-        drop(foo);
+        let other = double.clone();
+        let result = std::thread::spawn(move || other.call(21)).join().unwrap();
 
-        let a = std::thread::spawn(move || {
-            assert_eq!(*a, 42);
-        });
-        let b = std::thread::spawn(move || {
-            assert_eq!(*b, 1024);
+        assert_eq!(result, 42);
+        assert_eq!((*double)(4), 8);
+
+        let rc = Rc::new(Registry {
+            double: Box::new(|x| x * 2),
+            add: Box::new(|a, b| a + b),
         });
-        let c = std::thread::spawn(move || {
-            assert_eq!(*c, "Foo");
+        let add = RcReference::new(rc, |registry| &*registry.add);
+        assert_eq!(add.call(2, 3), 5);
+    }
+
+    #[test]
+    fn project_map() {
+        struct Record {
+            name: String,
+            id: u64,
+            tags: Vec<&'static str>,
+        }
+
+        let arc = Arc::new(Record {
+            name: String::from("Foo"),
+            id: 7,
+            tags: vec!["a", "b"],
         });
 
-        a.join().unwrap();
-        b.join().unwrap();
-        c.join().unwrap();
+        let fields = project_map!(arc => name, id, tags);
+        assert_eq!(Arc::strong_count(&arc), 4);
+        drop(arc);
+
+        assert_eq!(fields.len(), 3);
+        assert_eq!(fields["name"].downcast_ref::<String>().unwrap(), "Foo");
+        assert_eq!(fields["id"].downcast_ref::<u64>(), Some(&7));
+        assert_eq!(
+            fields["tags"].downcast_ref::<Vec<&str>>().unwrap(),
+            &["a", "b"]
+        );
+        assert!(fields["id"].downcast_ref::<u32>().is_none());
     }
 
     #[test]
-    fn test_multiple_rc() {
-        struct Foo {
-            a: u8,
-            b: u32,
-            c: String,
+    fn peek() {
+        let arc = Arc::new((String::from("Hello"), 42));
+
+        let len = ArcReference::peek(
+            &arc,
+            |(string, _)| string,
+            |string| {
+                assert_eq!(Arc::strong_count(&arc), 1);
+                string.len()
+            },
+        );
+
+        assert_eq!(len, 5);
+        assert_eq!(
+            ArcReference::peek(&arc, |(_, number)| number, |number| *number),
+            42
+        );
+        assert_eq!(Arc::strong_count(&arc), 1);
+    }
+
+    #[test]
+    fn erased_map_key() {
+        let string = Arc::new(String::from("alpha beta"));
+        let boxed: Arc<Box<str>> = Arc::new("gamma".into());
+
+        let mut map = HashMap::new();
+        map.insert(ArcReference::new(string.clone(), |s| &s[..5]).erase(), 1);
+        map.insert(ArcReference::new(string, |s| &s[6..]).erase(), 2);
+        map.insert(ArcReference::new(boxed, |s| &**s).erase(), 3);
+
+        assert_eq!(map.get("alpha"), Some(&1));
+        assert_eq!(map.get("beta"), Some(&2));
+        assert_eq!(map.get("gamma"), Some(&3));
+        assert_eq!(map.get("delta"), None);
+
+        let mut keys: Vec<_> = map.into_keys().collect();
+        keys.sort();
+        assert_eq!(
+            keys.iter().map(|key| &**key).collect::<Vec<_>>(),
+            ["alpha", "beta", "gamma"]
+        );
+    }
+
+    #[test]
+    fn records() {
+        fn length_prefixed(bytes: &[u8]) -> Option<(usize, Range<usize>)> {
+            let (&len, _) = bytes.split_first()?;
+            let end = 1 + len as usize;
+            (end <= bytes.len()).then_some((end, 1..end))
         }
 
-        let foo = Rc::new(Foo {
-            a: 42,
-            b: 1024,
-            c: String::from("Foo"),
-        });
+        let arc = Arc::new(b"\x03foo\x00\x06barbaz\x07trunc".to_vec());
+        let buffer = ArcReference::new(arc.clone(), |vec| &vec[..]);
+        drop(arc);
 
-        let (a, b, c) = rc_multiple(&foo, |ctx, value| {
-            (
-                ctx.new_reference(&value.a),
-                ctx.new_reference(&value.b),
-                ctx.new_reference(&value.c),
-            )
-        });
+        let records: Vec<_> = buffer.records(length_prefixed).collect();
+        assert_eq!(Arc::strong_count(buffer.source()), 4);
+        drop(buffer);
 
-        drop(foo);
+        let records: Vec<_> = records
+            .into_iter()
+            .map(|record| std::thread::spawn(move || record.to_vec()))
+            .map(|handle| handle.join().unwrap())
+            .collect();
 
-        assert_eq!(*a, 42);
-        assert_eq!(*b, 1024);
-        assert_eq!(*c, "Foo");
+        assert_eq!(records, [&b"foo"[..], b"", b"barbaz"]);
     }
 }
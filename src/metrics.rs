@@ -0,0 +1,48 @@
+//! Global counters of [`ArcReference`](crate::ArcReference)s, for spotting reference
+//! leaks in long-running processes.
+//!
+//! The counters are updated with relaxed atomics, so a [`snapshot`] taken while other
+//! threads create or drop references is only approximately consistent.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+static LIVE: AtomicUsize = AtomicUsize::new(0);
+static CREATED: AtomicUsize = AtomicUsize::new(0);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Metrics {
+    /// The number of `ArcReference`s currently alive, including clones.
+    pub live: usize,
+    /// The total number of `ArcReference`s created other than by cloning.
+    pub created: usize,
+}
+
+pub fn snapshot() -> Metrics {
+    Metrics {
+        live: LIVE.load(Ordering::Relaxed),
+        created: CREATED.load(Ordering::Relaxed),
+    }
+}
+
+pub(crate) struct Guard;
+
+impl Default for Guard {
+    fn default() -> Self {
+        LIVE.fetch_add(1, Ordering::Relaxed);
+        CREATED.fetch_add(1, Ordering::Relaxed);
+        Self
+    }
+}
+
+impl Clone for Guard {
+    fn clone(&self) -> Self {
+        LIVE.fetch_add(1, Ordering::Relaxed);
+        Self
+    }
+}
+
+impl Drop for Guard {
+    fn drop(&mut self) {
+        LIVE.fetch_sub(1, Ordering::Relaxed);
+    }
+}
@@ -0,0 +1,36 @@
+#![cfg(feature = "metrics")]
+
+use std::sync::Arc;
+
+use reference_arc::{metrics, ArcReference};
+
+#[test]
+fn snapshot() {
+    let before = metrics::snapshot();
+
+    let arc = Arc::new(String::from("Hello World!"));
+    let hello = ArcReference::new(arc.clone(), |string| &string[0..5]);
+    let world = ArcReference::new(arc.clone(), |string| &string[6..11]);
+    let clones: Vec<_> = (0..3).map(|_| hello.clone()).collect();
+
+    let weak = hello.downgrade();
+    assert_eq!(format!("{weak:?}"), "\"Hello\"");
+
+    let during = metrics::snapshot();
+    assert_eq!(during.live, before.live + 5);
+    assert_eq!(during.created, before.created + 2);
+
+    drop(clones);
+    drop(world);
+
+    let (prefix, rest) = ArcReference::new(arc, |string| string.as_bytes()).take_prefix(5);
+    drop(prefix);
+    drop(rest);
+
+    let after = metrics::snapshot();
+    assert_eq!(after.live, before.live + 1);
+    assert_eq!(after.created, before.created + 5);
+
+    drop(hello);
+    assert_eq!(metrics::snapshot().live, before.live);
+}